@@ -8,17 +8,33 @@ use rand::thread_rng;
 use std::io::{Error, ErrorKind};
 
 #[derive(Parser)]
-#[command(name = "ecvrf-cli")]
+#[command(name = "ecvrf-cli", version)]
 #[command(about = "Elliptic Curve Verifiable Random Function (ECVRF) over Ristretto255 according to draft-irtf-cfrg-vrf-15.", long_about = None)]
 enum Command {
     /// Generate a key pair for proving and verification.
     Keygen,
 
+    /// Derive the public key of a secret key.
+    Derive(DeriveArguments),
+
     /// Create an output/hash and a proof.
     Prove(ProveArguments),
 
     /// Verify an output/hash and a proof.
     Verify(VerifyArguments),
+
+    /// Print the VRF scheme identifier (curve, hash and suite string).
+    Scheme,
+}
+
+/// Identifier of the ECVRF suite implemented by `mangekyou::kamui_vrf::ecvrf`.
+const SCHEME: &str = "ECVRF-RISTRETTO255-SHA512/sol_vrf";
+
+#[derive(Parser, Clone)]
+struct DeriveArguments {
+    /// A hex encoding of the secret key. Corresponds to a scalar in Ristretto255 and must be 32 bytes.
+    #[clap(short, long)]
+    secret_key: String,
 }
 
 #[derive(Parser, Clone)]
@@ -80,6 +96,18 @@ fn execute(cmd: Command) -> Result<String, std::io::Error> {
             Ok(result)
         }
 
+        Command::Derive(arguments) => {
+            let secret_key_bytes = hex::decode(arguments.secret_key)
+                .map_err(|_| Error::new(ErrorKind::InvalidInput, "Invalid private key."))?;
+            let secret_key = ECVRFPrivateKey::from_bytes(&secret_key_bytes)
+                .map_err(|_| Error::new(ErrorKind::InvalidInput, "Invalid private key."))?;
+            let kp = ECVRFKeyPair::from(secret_key);
+
+            let mut result = "Public key: ".to_string();
+            result.push_str(&hex::encode(&kp.pk));
+            Ok(result)
+        }
+
         Command::Prove(arguments) => {
             // Parse inputs
             let secret_key_bytes = hex::decode(arguments.secret_key)
@@ -130,13 +158,15 @@ fn execute(cmd: Command) -> Result<String, std::io::Error> {
             }
             Err(Error::new(ErrorKind::Other, "Proof is not correct."))
         }
+
+        Command::Scheme => Ok(SCHEME.to_string()),
     }
 }
 
 #[cfg(test)]
 mod tests {
 
-    use crate::{execute, Command, ProveArguments, VerifyArguments};
+    use crate::{execute, Command, DeriveArguments, ProveArguments, VerifyArguments, SCHEME};
     use regex::Regex;
 
     #[test]
@@ -147,6 +177,18 @@ mod tests {
         assert!(expected.is_match(&result));
     }
 
+    #[test]
+    fn test_derive() {
+        let result = execute(Command::Derive(DeriveArguments {
+            secret_key: "d354a0525580ab79bf67797b824a7df3ddf81ff45729175fa4d98d9f3dcd150f".to_string(),
+        }))
+        .unwrap();
+        assert_eq!(
+            "Public key: 7a66a0fe0f2bcdcea5bfb97e3e9f6b298d25899052721bc2b4f3cb570a921b23",
+            result
+        );
+    }
+
     #[test]
     fn test_prove() {
         let secret_key = "d354a0525580ab79bf67797b824a7df3ddf81ff45729175fa4d98d9f3dcd150f";
@@ -192,4 +234,9 @@ mod tests {
         }));
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_scheme() {
+        assert_eq!(SCHEME, execute(Command::Scheme).unwrap());
+    }
 }
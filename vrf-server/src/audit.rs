@@ -0,0 +1,235 @@
+use {
+    crate::enhanced_vrf_server::parse_vrf_result_account,
+    log::{debug, error, info, warn},
+    serde::Serialize,
+    solana_client::rpc_client::RpcClient,
+    solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey},
+    std::{
+        collections::VecDeque,
+        error::Error,
+        sync::{Arc, Mutex},
+        time::Duration,
+    },
+    tokio::task::JoinHandle,
+};
+
+/// Number of recent fulfillments kept in the audit ring buffer
+pub const AUDIT_LOG_CAPACITY: usize = 256;
+
+/// Number of most recent fulfillments re-checked on every audit pass
+pub const AUDIT_SAMPLE_SIZE: usize = 16;
+
+/// A fulfillment as submitted by this server
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AuditRecord {
+    pub request: Pubkey,
+    /// Hex-encoded VRF output that was submitted
+    pub output: String,
+    pub signature: String,
+    /// Oracle-signed attestation of the result, when enabled
+    pub attestation: Option<String>,
+}
+
+/// Bounded ring buffer of recent fulfillments; the oldest entry is dropped when full
+#[derive(Debug)]
+pub struct AuditLog {
+    records: VecDeque<AuditRecord>,
+    capacity: usize,
+}
+
+impl AuditLog {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            records: VecDeque::with_capacity(capacity),
+            capacity: capacity.max(1),
+        }
+    }
+
+    pub fn record(&mut self, record: AuditRecord) {
+        if self.records.len() == self.capacity {
+            self.records.pop_front();
+        }
+        self.records.push_back(record);
+    }
+
+    /// Up to `n` of the most recent records, newest first
+    pub fn recent(&self, n: usize) -> Vec<AuditRecord> {
+        self.records.iter().rev().take(n).cloned().collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+}
+
+impl Default for AuditLog {
+    fn default() -> Self {
+        Self::new(AUDIT_LOG_CAPACITY)
+    }
+}
+
+/// On-chain state that does not match what the server submitted
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AuditDiscrepancy {
+    pub request: String,
+    pub result_account: String,
+    /// Output the server submitted
+    pub expected: String,
+    /// Output stored on-chain, or `None` if the result account is missing or unreadable
+    pub found: Option<String>,
+}
+
+/// Periodically re-reads the result accounts of recent fulfillments and
+/// checks that the stored output is the one the server submitted
+pub struct FulfillmentAuditor {
+    rpc_client: Arc<RpcClient>,
+    program_id: Pubkey,
+    commitment: CommitmentConfig,
+    audit_log: Arc<Mutex<AuditLog>>,
+}
+
+impl FulfillmentAuditor {
+    pub fn new(
+        rpc_client: Arc<RpcClient>,
+        program_id: Pubkey,
+        commitment: CommitmentConfig,
+        audit_log: Arc<Mutex<AuditLog>>,
+    ) -> Self {
+        Self {
+            rpc_client,
+            program_id,
+            commitment,
+            audit_log,
+        }
+    }
+
+    /// Check the most recent fulfillments once, logging and returning every discrepancy
+    pub fn audit_once(&self) -> Result<Vec<AuditDiscrepancy>, Box<dyn Error>> {
+        let sample = self.audit_log.lock().unwrap().recent(AUDIT_SAMPLE_SIZE);
+        if sample.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let result_accounts: Vec<Pubkey> = sample
+            .iter()
+            .map(|record| {
+                Pubkey::find_program_address(&[b"vrf_result", record.request.as_ref()], &self.program_id).0
+            })
+            .collect();
+        let accounts = self
+            .rpc_client
+            .get_multiple_accounts_with_commitment(&result_accounts, self.commitment)?
+            .value;
+
+        let mut discrepancies = Vec::new();
+        for ((record, result_account), account) in sample.iter().zip(&result_accounts).zip(accounts) {
+            let found = account.and_then(|account| match parse_vrf_result_account(&account.data) {
+                Ok(result) => result.randomness.first().map(hex::encode),
+                Err(e) => {
+                    warn!("⚠️  Audit could not parse result account {}: {}", result_account, e);
+                    None
+                }
+            });
+            if found.as_deref() != Some(record.output.as_str()) {
+                error!(
+                    "🚨 Audit discrepancy for request {}: submitted output {} in tx {}, result account {} holds {}",
+                    record.request,
+                    record.output,
+                    record.signature,
+                    result_account,
+                    found.as_deref().unwrap_or("nothing"),
+                );
+                discrepancies.push(AuditDiscrepancy {
+                    request: record.request.to_string(),
+                    result_account: result_account.to_string(),
+                    expected: record.output.clone(),
+                    found,
+                });
+            }
+        }
+
+        debug!("🔎 Audited {} fulfillments, {} discrepancies", sample.len(), discrepancies.len());
+        Ok(discrepancies)
+    }
+
+    /// Run `audit_once` every `interval` on a background task
+    pub fn spawn(self: Arc<Self>, interval: Duration) -> JoinHandle<()> {
+        info!("🔎 Auditing recent fulfillments on-chain every {:?}", interval);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            // The first tick completes immediately; nothing has been fulfilled yet
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                let auditor = Arc::clone(&self);
+                match tokio::task::spawn_blocking(move || auditor.audit_once().map_err(|e| e.to_string())).await {
+                    Ok(Ok(_)) => {}
+                    Ok(Err(e)) => warn!("⚠️  Fulfillment audit failed: {}", e),
+                    Err(e) => warn!("⚠️  Fulfillment audit task panicked: {}", e),
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{enhanced_vrf_server::VRF_RESULT_DISCRIMINATOR, state::VrfResult},
+        solana_account_decoder::{UiAccount, UiAccountEncoding},
+        solana_client::rpc_request::RpcRequest,
+        solana_sdk::account::Account,
+        std::collections::HashMap,
+    };
+
+    fn result_account(output: [u8; 64]) -> Account {
+        let mut data = VRF_RESULT_DISCRIMINATOR.to_vec();
+        data.extend_from_slice(&borsh::to_vec(&VrfResult { randomness: vec![output], proof: vec![0; 80], proof_block: 1 }).unwrap());
+        Account { lamports: 1, data, owner: Pubkey::new_unique(), executable: false, rent_epoch: 0 }
+    }
+
+    #[test]
+    fn test_auditor_reports_divergent_result() {
+        let program_id = Pubkey::new_unique();
+        let (honest, tampered) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let audit_log = Arc::new(Mutex::new(AuditLog::default()));
+        for request in [honest, tampered] {
+            audit_log.lock().unwrap().record(AuditRecord {
+                request,
+                output: hex::encode([1u8; 64]),
+                signature: "sig".to_string(),
+                attestation: None,
+            });
+        }
+
+        // Newest first: the tampered request is sampled before the honest one
+        let encode = |account: &Account| {
+            UiAccount::encode(&Pubkey::new_unique(), account, UiAccountEncoding::Base64, None, None)
+        };
+        let mut mocks = HashMap::new();
+        mocks.insert(
+            RpcRequest::GetMultipleAccounts,
+            serde_json::json!({
+                "context": { "slot": 1 },
+                "value": [encode(&result_account([2u8; 64])), encode(&result_account([1u8; 64]))],
+            }),
+        );
+        let auditor = FulfillmentAuditor::new(
+            Arc::new(RpcClient::new_mock_with_mocks("succeeds".to_string(), mocks)),
+            program_id,
+            CommitmentConfig::confirmed(),
+            audit_log,
+        );
+
+        let discrepancies = auditor.audit_once().unwrap();
+        assert_eq!(discrepancies.len(), 1);
+        assert_eq!(discrepancies[0].request, tampered.to_string());
+        assert_eq!(discrepancies[0].expected, hex::encode([1u8; 64]));
+        assert_eq!(discrepancies[0].found, Some(hex::encode([2u8; 64])));
+    }
+}
@@ -0,0 +1,58 @@
+use {serde::Serialize, std::time::Duration};
+
+/// Latency percentiles of a bench run, in milliseconds
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct LatencyPercentiles {
+    pub p50: u64,
+    pub p90: u64,
+    pub p99: u64,
+    pub max: u64,
+}
+
+impl LatencyPercentiles {
+    pub fn from_samples(mut samples: Vec<u64>) -> Self {
+        samples.sort_unstable();
+        Self {
+            p50: percentile(&samples, 50),
+            p90: percentile(&samples, 90),
+            p99: percentile(&samples, 99),
+            max: samples.last().copied().unwrap_or(0),
+        }
+    }
+}
+
+/// Nearest-rank percentile of already sorted samples
+pub fn percentile(sorted: &[u64], p: usize) -> u64 {
+    if sorted.is_empty() {
+        return 0;
+    }
+    let rank = (p * sorted.len()).div_ceil(100).max(1);
+    sorted[rank.min(sorted.len()) - 1]
+}
+
+/// End-to-end fulfillment throughput measured by the `bench` subcommand
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchReport {
+    pub requests: usize,
+    pub fulfilled: usize,
+    pub failed: usize,
+    pub duration_ms: u64,
+    /// Fulfilled requests per second
+    pub throughput: f64,
+    /// Time from the start of the run to each request's outcome
+    pub latency_ms: LatencyPercentiles,
+}
+
+impl BenchReport {
+    pub fn new(requests: usize, fulfilled: usize, duration: Duration, latencies_ms: Vec<u64>) -> Self {
+        let secs = duration.as_secs_f64();
+        Self {
+            requests,
+            fulfilled,
+            failed: requests - fulfilled,
+            duration_ms: duration.as_millis() as u64,
+            throughput: if secs > 0.0 { fulfilled as f64 / secs } else { 0.0 },
+            latency_ms: LatencyPercentiles::from_samples(latencies_ms),
+        }
+    }
+}
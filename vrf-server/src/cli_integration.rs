@@ -1,9 +1,12 @@
 use {
     std::{
-        process::{Command, Stdio},
+        process::{Child, Command, Output, Stdio},
         path::Path,
         error::Error,
         fmt,
+        io::Read,
+        thread,
+        time::{Duration, Instant},
     },
     log::{debug, error, info, warn},
     serde::{Deserialize, Serialize},
@@ -14,6 +17,8 @@ pub enum CLIError {
     ProcessError(std::io::Error),
     InvalidOutput(String),
     ProofGenerationFailed(String),
+    /// The CLI didn't exit within the configured timeout and was killed
+    Timeout(Duration),
 }
 
 impl fmt::Display for CLIError {
@@ -22,6 +27,7 @@ impl fmt::Display for CLIError {
             CLIError::ProcessError(e) => write!(f, "CLI process error: {}", e),
             CLIError::InvalidOutput(msg) => write!(f, "Invalid CLI output: {}", msg),
             CLIError::ProofGenerationFailed(msg) => write!(f, "Proof generation failed: {}", msg),
+            CLIError::Timeout(timeout) => write!(f, "CLI did not exit within {:?} and was killed", timeout),
         }
     }
 }
@@ -33,10 +39,244 @@ pub struct VRFCliProof {
     pub proof: String,
     pub output: String,
     pub public_key: String,
+    /// Structured proof components, when the CLI printed them (verbose proofs)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub components: Option<VrfProofComponents>,
 }
 
+impl VRFCliProof {
+    /// The proof's (gamma, c, s) components, as printed by the CLI or split from the proof bytes
+    pub fn proof_components(&self) -> Result<VrfProofComponents, CLIError> {
+        match &self.components {
+            Some(components) => Ok(components.clone()),
+            None => VrfProofComponents::from_proof_hex(&self.proof),
+        }
+    }
+}
+
+/// Length of the encoded gamma point in an ECVRF proof
+const PROOF_GAMMA_LEN: usize = 32;
+/// Length of the challenge scalar `c`
+const PROOF_C_LEN: usize = 16;
+/// Length of the response scalar `s`
+const PROOF_S_LEN: usize = 32;
+
+/// ECVRF proof components (hex encoded); the proof is `gamma || c || s`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VrfProofComponents {
+    pub gamma: String,
+    pub c: String,
+    pub s: String,
+}
+
+impl VrfProofComponents {
+    /// Split an encoded proof into its components
+    pub fn from_proof_hex(proof: &str) -> Result<Self, CLIError> {
+        let bytes = hex::decode(proof).map_err(|e| CLIError::InvalidOutput(format!("Invalid proof hex: {}", e)))?;
+        if bytes.len() != PROOF_GAMMA_LEN + PROOF_C_LEN + PROOF_S_LEN {
+            return Err(CLIError::InvalidOutput(format!("Unexpected proof length {}", bytes.len())));
+        }
+        let (gamma, rest) = bytes.split_at(PROOF_GAMMA_LEN);
+        let (c, s) = rest.split_at(PROOF_C_LEN);
+        Ok(Self { gamma: hex::encode(gamma), c: hex::encode(c), s: hex::encode(s) })
+    }
+
+    pub fn gamma_bytes(&self) -> Result<Vec<u8>, CLIError> {
+        decode_component(&self.gamma, "gamma", PROOF_GAMMA_LEN)
+    }
+
+    pub fn c_bytes(&self) -> Result<Vec<u8>, CLIError> {
+        decode_component(&self.c, "c", PROOF_C_LEN)
+    }
+
+    pub fn s_bytes(&self) -> Result<Vec<u8>, CLIError> {
+        decode_component(&self.s, "s", PROOF_S_LEN)
+    }
+
+    /// Recombine the components into the encoded proof bytes
+    pub fn to_proof_bytes(&self) -> Result<Vec<u8>, CLIError> {
+        Ok([self.gamma_bytes()?, self.c_bytes()?, self.s_bytes()?].concat())
+    }
+}
+
+fn decode_component(value: &str, name: &str, len: usize) -> Result<Vec<u8>, CLIError> {
+    let bytes = hex::decode(value).map_err(|e| CLIError::InvalidOutput(format!("Invalid proof {} hex: {}", name, e)))?;
+    if bytes.len() != len {
+        return Err(CLIError::InvalidOutput(format!("Proof {} must be {} bytes, got {}", name, len, bytes.len())));
+    }
+    Ok(bytes)
+}
+
+/// Environment variable names whose values are never logged
+const SECRET_ENV_MARKERS: &[&str] = &["SECRET", "TOKEN", "PASSWORD", "PASSPHRASE", "PIN", "KEY", "CREDENTIAL"];
+
+/// Parse a `KEY=VALUE` environment variable for the CLI subprocess
+pub fn parse_cli_env(s: &str) -> Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.to_string(), value.to_string())),
+        _ => Err(format!("invalid environment variable '{}', expected KEY=VALUE", s)),
+    }
+}
+
+/// `KEY=VALUE` for logs, with the value masked if the name looks secret
+pub fn redact_env_var(key: &str, value: &str) -> String {
+    let upper = key.to_ascii_uppercase();
+    if SECRET_ENV_MARKERS.iter().any(|marker| upper.contains(marker)) {
+        format!("{}=***", key)
+    } else {
+        format!("{}={}", key, value)
+    }
+}
+
+/// Output format of `ecvrf-cli`, as reported by `ecvrf-cli --output-format-version`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CliOutputSchema {
+    /// `Secret key: <hex>` / `Proof:  <hex>` text lines; CLIs predating the
+    /// version flag print this
+    #[default]
+    V1,
+    /// One JSON object per command
+    V2,
+}
+
+/// Output format versions this server can parse
+pub const SUPPORTED_CLI_OUTPUT_SCHEMAS: &[u32] = &[1, 2];
+
+#[derive(Deserialize)]
+struct KeygenOutputV2 {
+    secret_key: String,
+    public_key: String,
+}
+
+#[derive(Deserialize)]
+struct DeriveOutputV2 {
+    public_key: String,
+}
+
+#[derive(Deserialize)]
+struct ProveOutputV2 {
+    proof: String,
+    output: String,
+    /// Only printed in verbose mode
+    #[serde(flatten)]
+    components: Option<VrfProofComponents>,
+}
+
+impl CliOutputSchema {
+    pub fn from_version(version: u32) -> Result<Self, CLIError> {
+        match version {
+            1 => Ok(CliOutputSchema::V1),
+            2 => Ok(CliOutputSchema::V2),
+            _ => Err(CLIError::InvalidOutput(format!(
+                "Unsupported CLI output format version {} (supported: {:?})", version, SUPPORTED_CLI_OUTPUT_SCHEMAS
+            ))),
+        }
+    }
+
+    /// Parse `keygen` output into (secret_key, public_key)
+    pub fn parse_keypair(&self, stdout: &str) -> Result<(String, String), CLIError> {
+        match self {
+            // "Secret key: <hex>\nPublic key: <hex>"
+            CliOutputSchema::V1 => {
+                let lines = KeyedLines::parse(stdout);
+                Ok((lines.required("Secret key", stdout)?, lines.required("Public key", stdout)?))
+            }
+            CliOutputSchema::V2 => {
+                let keypair: KeygenOutputV2 = serde_json::from_str(stdout.trim())
+                    .map_err(|e| CLIError::InvalidOutput(format!("Invalid keygen JSON: {}: {}", e, stdout)))?;
+                Ok((keypair.secret_key, keypair.public_key))
+            }
+        }
+    }
+
+    /// Parse `derive` output into the public key
+    pub fn parse_public_key(&self, stdout: &str) -> Result<String, CLIError> {
+        match self {
+            // "Public key: <hex>"
+            CliOutputSchema::V1 => KeyedLines::parse(stdout).required("Public key", stdout),
+            CliOutputSchema::V2 => {
+                let derived: DeriveOutputV2 = serde_json::from_str(stdout.trim())
+                    .map_err(|e| CLIError::InvalidOutput(format!("Invalid derive JSON: {}: {}", e, stdout)))?;
+                Ok(derived.public_key)
+            }
+        }
+    }
+
+    /// Parse `prove` output into (proof, output, components printed in verbose mode)
+    pub fn parse_proof(&self, stdout: &str) -> Result<(String, String, Option<VrfProofComponents>), CLIError> {
+        match self {
+            // "Proof:  <hex>\nOutput: <hex>", then "Gamma: <hex>\nC: <hex>\nS: <hex>" in verbose mode
+            CliOutputSchema::V1 => {
+                let lines = KeyedLines::parse(stdout);
+                let proof = lines.required("Proof", stdout)?;
+                let output = lines.required("Output", stdout)?;
+                let components = match (lines.get("Gamma"), lines.get("C"), lines.get("S")) {
+                    (None, None, None) => None,
+                    (Some(gamma), Some(c), Some(s)) => Some(VrfProofComponents {
+                        gamma: gamma.to_string(),
+                        c: c.to_string(),
+                        s: s.to_string(),
+                    }),
+                    _ => {
+                        return Err(CLIError::InvalidOutput(format!(
+                            "Expected all or none of 'Gamma', 'C' and 'S': {}", stdout
+                        )));
+                    }
+                };
+                Ok((proof, output, components))
+            }
+            CliOutputSchema::V2 => {
+                let proof: ProveOutputV2 = serde_json::from_str(stdout.trim())
+                    .map_err(|e| CLIError::InvalidOutput(format!("Invalid prove JSON: {}: {}", e, stdout)))?;
+                Ok((proof.proof, proof.output, proof.components))
+            }
+        }
+    }
+}
+
+/// `Key: value` lines of text output. Lines are trimmed and split on their
+/// first `:`; blank lines and lines without one are ignored, so stray log
+/// lines, spacing changes and reordering don't break parsing.
+struct KeyedLines<'a>(Vec<(&'a str, &'a str)>);
+
+impl<'a> KeyedLines<'a> {
+    fn parse(stdout: &'a str) -> Self {
+        Self(stdout
+            .lines()
+            .filter_map(|line| line.trim().split_once(':'))
+            .map(|(key, value)| (key.trim(), value.trim()))
+            .collect())
+    }
+
+    /// Value of the first line with `key` (case-insensitive)
+    fn get(&self, key: &str) -> Option<&'a str> {
+        self.0.iter().find(|(k, _)| k.eq_ignore_ascii_case(key)).map(|(_, value)| *value)
+    }
+
+    fn required(&self, key: &str, stdout: &str) -> Result<String, CLIError> {
+        match self.get(key) {
+            Some(value) if !value.is_empty() => Ok(value.to_string()),
+            _ => Err(CLIError::InvalidOutput(format!("Missing '{}:' line in CLI output: {}", key, stdout))),
+        }
+    }
+}
+
+/// How long a CLI invocation may run before it's killed
+pub const DEFAULT_CLI_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How often a running CLI is checked for exit
+const CLI_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
 pub struct MangekyouCLI {
     cli_path: String,
+    /// Extra environment variables set on every CLI invocation
+    env: Vec<(String, String)>,
+    /// Parser matching the CLI's output format
+    output_schema: CliOutputSchema,
+    /// Ask the CLI to also print the proof components
+    verbose_proofs: bool,
+    /// Per-invocation limit; a CLI still running after it is killed
+    timeout: Duration,
 }
 
 impl MangekyouCLI {
@@ -44,7 +284,106 @@ impl MangekyouCLI {
         let default_path = "../mangekyou-cli/target/debug/ecvrf-cli".to_string();
         Self {
             cli_path: cli_path.unwrap_or(default_path),
+            env: Vec::new(),
+            output_schema: CliOutputSchema::default(),
+            verbose_proofs: false,
+            timeout: DEFAULT_CLI_TIMEOUT,
+        }
+    }
+
+    /// Kill any CLI invocation still running after `timeout`
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Capture the (gamma, c, s) proof components with every proof; the CLI
+    /// must support `prove --verbose`
+    pub fn with_verbose_proofs(mut self, verbose_proofs: bool) -> Self {
+        self.verbose_proofs = verbose_proofs;
+        self
+    }
+
+    /// Parse CLI output with the given schema
+    pub fn with_output_schema(mut self, output_schema: CliOutputSchema) -> Self {
+        self.output_schema = output_schema;
+        self
+    }
+
+    pub fn output_schema(&self) -> CliOutputSchema {
+        self.output_schema
+    }
+
+    /// Ask the CLI which output format it prints.
+    ///
+    /// CLIs that reject `--output-format-version` predate it and print the
+    /// original text format. A version this server can't parse is an error.
+    pub fn query_output_schema(&self) -> Result<CliOutputSchema, CLIError> {
+        let mut command = self.command();
+        command.arg("--output-format-version");
+        let output = self.run(command)?;
+
+        if !output.status.success() {
+            debug!("CLI does not report an output format version, assuming version 1");
+            return Ok(CliOutputSchema::V1);
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let version = stdout.trim().parse::<u32>().map_err(|e| CLIError::InvalidOutput(format!(
+            "Invalid CLI output format version '{}': {}", stdout.trim(), e
+        )))?;
+        let schema = CliOutputSchema::from_version(version)?;
+        info!("CLI output format version {} ({:?})", version, schema);
+        Ok(schema)
+    }
+
+    /// Set these environment variables on every CLI invocation, on top of the inherited environment
+    pub fn with_env(mut self, env: Vec<(String, String)>) -> Self {
+        for (key, value) in &env {
+            info!("CLI environment: {}", redact_env_var(key, value));
         }
+        self.env = env;
+        self
+    }
+
+    /// A command running the CLI with the configured environment
+    fn command(&self) -> Command {
+        let mut command = Command::new(&self.cli_path);
+        command.envs(self.env.iter().map(|(key, value)| (key, value)));
+        command
+    }
+
+    /// Run `command` to completion, collecting its output, or kill it once
+    /// the timeout passes. Stdin is closed so a CLI waiting for input fails
+    /// instead of hanging.
+    fn run(&self, mut command: Command) -> Result<Output, CLIError> {
+        let mut child = command
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(CLIError::ProcessError)?;
+        // Drain the pipes while waiting so a chatty CLI can't block on a full pipe
+        let stdout = child.stdout.take().map(read_in_background);
+        let stderr = child.stderr.take().map(read_in_background);
+
+        let deadline = Instant::now() + self.timeout;
+        let status = loop {
+            if let Some(status) = child.try_wait().map_err(CLIError::ProcessError)? {
+                break status;
+            }
+            if Instant::now() >= deadline {
+                kill(&mut child);
+                warn!("CLI did not exit within {:?}, killed it", self.timeout);
+                return Err(CLIError::Timeout(self.timeout));
+            }
+            thread::sleep(CLI_POLL_INTERVAL);
+        };
+
+        let collect = |reader: Option<thread::JoinHandle<Vec<u8>>>| {
+            reader.map(|reader| reader.join().unwrap_or_default()).unwrap_or_default()
+        };
+        Ok(Output { status, stdout: collect(stdout), stderr: collect(stderr) })
     }
 
     /// Build the CLI if it doesn't exist
@@ -80,12 +419,9 @@ impl MangekyouCLI {
     pub fn generate_keypair(&self) -> Result<(String, String), CLIError> {
         debug!("Generating new VRF keypair using CLI");
         
-        let output = Command::new(&self.cli_path)
-            .arg("keygen")
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .output()
-            .map_err(CLIError::ProcessError)?;
+        let mut command = self.command();
+        command.arg("keygen");
+        let output = self.run(command)?;
 
         if !output.status.success() {
             let stderr = String::from_utf8_lossy(&output.stderr);
@@ -97,23 +433,7 @@ impl MangekyouCLI {
         let stdout = String::from_utf8_lossy(&output.stdout);
         debug!("CLI keygen output: {}", stdout);
 
-        // Parse the output: "Secret key: <hex>\nPublic key: <hex>"
-        let lines: Vec<&str> = stdout.trim().lines().collect();
-        if lines.len() != 2 {
-            return Err(CLIError::InvalidOutput(format!(
-                "Expected 2 lines, got {}: {}", lines.len(), stdout
-            )));
-        }
-
-        let secret_key = lines[0]
-            .strip_prefix("Secret key: ")
-            .ok_or_else(|| CLIError::InvalidOutput("Missing secret key prefix".to_string()))?
-            .to_string();
-
-        let public_key = lines[1]
-            .strip_prefix("Public key: ")
-            .ok_or_else(|| CLIError::InvalidOutput("Missing public key prefix".to_string()))?
-            .to_string();
+        let (secret_key, public_key) = self.output_schema.parse_keypair(&stdout)?;
 
         info!("Generated VRF keypair - Public key: {}", public_key);
         Ok((secret_key, public_key))
@@ -124,16 +444,17 @@ impl MangekyouCLI {
         let input_hex = hex::encode(input);
         debug!("Generating VRF proof for input: {}", input_hex);
 
-        let output = Command::new(&self.cli_path)
-            .arg("prove")
+        let mut command = self.command();
+        command.arg("prove");
+        if self.verbose_proofs {
+            command.arg("--verbose");
+        }
+        command
             .arg("--input")
             .arg(&input_hex)
             .arg("--secret-key")
-            .arg(secret_key)
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .output()
-            .map_err(CLIError::ProcessError)?;
+            .arg(secret_key);
+        let output = self.run(command)?;
 
         if !output.status.success() {
             let stderr = String::from_utf8_lossy(&output.stderr);
@@ -145,31 +466,21 @@ impl MangekyouCLI {
         let stdout = String::from_utf8_lossy(&output.stdout);
         debug!("CLI prove output: {}", stdout);
 
-        // Parse the output: "Proof: <hex>\nOutput: <hex>"
-        let lines: Vec<&str> = stdout.trim().lines().collect();
-        if lines.len() != 2 {
-            return Err(CLIError::InvalidOutput(format!(
-                "Expected 2 lines, got {}: {}", lines.len(), stdout
-            )));
+        let (proof, output_hash, components) = self.output_schema.parse_proof(&stdout)?;
+        if let Some(components) = &components {
+            if hex::encode(components.to_proof_bytes()?) != proof {
+                return Err(CLIError::InvalidOutput("Proof components don't recombine into the proof".to_string()));
+            }
         }
 
-        let proof = lines[0]
-            .strip_prefix("Proof:  ")
-            .ok_or_else(|| CLIError::InvalidOutput("Missing proof prefix".to_string()))?
-            .to_string();
-
-        let output_hash = lines[1]
-            .strip_prefix("Output: ")
-            .ok_or_else(|| CLIError::InvalidOutput("Missing output prefix".to_string()))?
-            .to_string();
-
         // Derive public key from secret key (we'll need this for verification)
-        let (_, public_key) = self.derive_public_key(secret_key)?;
+        let public_key = self.derive_public_key(secret_key)?;
 
         let result = VRFCliProof {
             proof,
             output: output_hash,
             public_key,
+            components,
         };
 
         info!("Generated VRF proof successfully");
@@ -190,7 +501,8 @@ impl MangekyouCLI {
         let input_hex = hex::encode(input);
         debug!("Verifying VRF proof");
 
-        let cli_output = Command::new(&self.cli_path)
+        let mut command = self.command();
+        command
             .arg("verify")
             .arg("--proof")
             .arg(proof)
@@ -199,11 +511,8 @@ impl MangekyouCLI {
             .arg("--public-key")
             .arg(public_key)
             .arg("--input")
-            .arg(&input_hex)
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .output()
-            .map_err(CLIError::ProcessError)?;
+            .arg(&input_hex);
+        let cli_output = self.run(command)?;
 
         let success = cli_output.status.success();
         
@@ -217,13 +526,81 @@ impl MangekyouCLI {
         Ok(success)
     }
 
-    /// Derive public key from secret key
-    fn derive_public_key(&self, secret_key: &str) -> Result<(String, String), CLIError> {
-        // For now, we'll use keygen and match - in production, we'd implement proper key derivation
-        // This is a temporary approach since the CLI doesn't have a dedicated derive command
-        warn!("Using keygen for public key derivation - this should be optimized for production");
-        self.generate_keypair()
+    /// Query the CLI for its VRF scheme identifier
+    pub fn scheme(&self) -> Result<String, CLIError> {
+        let mut command = self.command();
+        command.arg("scheme");
+        let output = self.run(command)?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(CLIError::InvalidOutput(format!(
+                "Scheme query failed (CLI may predate the scheme command): {}", stderr
+            )));
+        }
+
+        let scheme = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        if scheme.is_empty() {
+            return Err(CLIError::InvalidOutput("Empty scheme identifier".to_string()));
+        }
+        Ok(scheme)
+    }
+
+    /// Version reported by `ecvrf-cli --version`, without the program name
+    pub fn version(&self) -> Result<String, CLIError> {
+        let mut command = self.command();
+        command.arg("--version");
+        let output = self.run(command)?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(CLIError::InvalidOutput(format!(
+                "Version query failed (CLI may predate --version): {}", stderr
+            )));
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        stdout.split_whitespace().last()
+            .map(str::to_string)
+            .ok_or_else(|| CLIError::InvalidOutput("Empty version".to_string()))
     }
+
+    /// Derive the public key matching `secret_key` using the CLI
+    pub fn derive_public_key(&self, secret_key: &str) -> Result<String, CLIError> {
+        let mut command = self.command();
+        command
+            .arg("derive")
+            .arg("--secret-key")
+            .arg(secret_key);
+        let output = self.run(command)?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(CLIError::ProofGenerationFailed(format!(
+                "Public key derivation failed: {}", stderr
+            )));
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        debug!("CLI derive output: {}", stdout);
+        self.output_schema.parse_public_key(&stdout)
+    }
+}
+
+fn read_in_background(mut pipe: impl Read + Send + 'static) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut buffer = Vec::new();
+        let _ = pipe.read_to_end(&mut buffer);
+        buffer
+    })
+}
+
+fn kill(child: &mut Child) {
+    if let Err(e) = child.kill() {
+        warn!("Failed to kill CLI process {}: {}", child.id(), e);
+    }
+    // Reap it so it doesn't linger as a zombie
+    let _ = child.wait();
 }
 
 #[cfg(test)]
@@ -232,22 +609,169 @@ mod tests {
 
     #[test]
     fn test_cli_integration() {
+        use crate::prover::VrfBackend;
+
+        let provers = [VrfBackend::Cli.build(None), VrfBackend::InProcess.build(None)];
+        let input = b"test input";
+        for prover in &provers {
+            // Test keygen
+            let (secret_key, public_key) = prover.generate_keypair().expect("Failed to generate keypair");
+            assert!(!secret_key.is_empty());
+            assert!(!public_key.is_empty());
+            assert_eq!(prover.derive_public_key(&secret_key).expect("Failed to derive public key"), public_key);
+
+            // Test proof generation
+            let proof = prover.generate_proof(&secret_key, input).expect("Failed to generate proof");
+            assert!(!proof.proof.is_empty());
+            assert!(!proof.output.is_empty());
+
+            // Both implementations accept the proof, and prove the same output for the same key
+            for verifier in &provers {
+                let is_valid = verifier.verify_proof(&proof.proof, &proof.output, &proof.public_key, input)
+                    .expect("Failed to verify proof");
+                assert!(is_valid, "{} proof rejected by {}", prover.name(), verifier.name());
+                assert_eq!(verifier.generate_proof(&secret_key, input).unwrap().output, proof.output);
+            }
+        }
+    }
+
+    #[test]
+    fn test_derived_public_key_matches_keygen() {
         let cli = MangekyouCLI::new(None);
-        
-        // Test keygen
         let (secret_key, public_key) = cli.generate_keypair().expect("Failed to generate keypair");
-        assert!(!secret_key.is_empty());
-        assert!(!public_key.is_empty());
-        
-        // Test proof generation
-        let input = b"test input";
-        let proof = cli.generate_proof(&secret_key, input).expect("Failed to generate proof");
-        assert!(!proof.proof.is_empty());
-        assert!(!proof.output.is_empty());
-        
-        // Test verification
-        let is_valid = cli.verify_proof(&proof.proof, &proof.output, &proof.public_key, input)
-            .expect("Failed to verify proof");
-        assert!(is_valid);
+        assert_eq!(cli.derive_public_key(&secret_key).expect("Failed to derive public key"), public_key);
+
+        // Proofs carry the key they were made with
+        let proof = cli.generate_proof(&secret_key, b"test input").expect("Failed to generate proof");
+        assert_eq!(proof.public_key, public_key);
+
+        assert_eq!(CliOutputSchema::V1.parse_public_key("Public key: ab12\n").unwrap(), "ab12");
+        assert_eq!(CliOutputSchema::V2.parse_public_key(r#"{"public_key": "ab12"}"#).unwrap(), "ab12");
+        assert!(CliOutputSchema::V1.parse_public_key("Secret key: cd34\n").is_err());
+    }
+
+    #[test]
+    fn test_cli_env_reaches_subprocess() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let stub = std::env::temp_dir().join(format!("kamui-env-cli-{}.sh", rand::random::<u64>()));
+        std::fs::write(&stub, "#!/bin/sh\necho \"$KAMUI_CLI_CONFIG:$KAMUI_HSM_PIN\"\n").unwrap();
+        std::fs::set_permissions(&stub, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let cli = MangekyouCLI::new(Some(stub.to_string_lossy().to_string())).with_env(vec![
+            parse_cli_env("KAMUI_CLI_CONFIG=/etc/kamui.toml").unwrap(),
+            parse_cli_env("KAMUI_HSM_PIN=1234").unwrap(),
+        ]);
+        let echoed = cli.scheme().unwrap();
+        std::fs::remove_file(&stub).unwrap();
+
+        assert_eq!(echoed, "/etc/kamui.toml:1234");
+        assert_eq!(redact_env_var("KAMUI_HSM_PIN", "1234"), "KAMUI_HSM_PIN=***");
+        assert_eq!(redact_env_var("RUST_BACKTRACE", "1"), "RUST_BACKTRACE=1");
+        assert!(parse_cli_env("NO_VALUE").is_err());
+    }
+
+    #[test]
+    fn test_hung_cli_is_killed_after_timeout() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let stub = std::env::temp_dir().join(format!("kamui-hung-cli-{}.sh", rand::random::<u64>()));
+        std::fs::write(&stub, "#!/bin/sh\nif [ \"$1\" = scheme ]; then echo ecvrf; exit 0; fi\nexec sleep 30\n").unwrap();
+        std::fs::set_permissions(&stub, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let cli = MangekyouCLI::new(Some(stub.to_string_lossy().to_string()))
+            .with_timeout(Duration::from_millis(200));
+        assert_eq!(cli.scheme().unwrap(), "ecvrf");
+
+        let started = Instant::now();
+        let result = cli.generate_proof("ab", b"seed");
+        std::fs::remove_file(&stub).unwrap();
+
+        assert!(matches!(result, Err(CLIError::Timeout(timeout)) if timeout == Duration::from_millis(200)));
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+
+    #[test]
+    fn test_output_schema_version_selects_parser() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let stub_cli = |version: &str, keygen: &str, derive: &str, prove: &str| {
+            let stub = std::env::temp_dir().join(format!("kamui-schema-cli-{}.sh", rand::random::<u64>()));
+            std::fs::write(&stub, format!(
+                "#!/bin/sh\ncase \"$1\" in\n--output-format-version) {} ;;\nkeygen) printf '{}' ;;\nderive) printf '{}' ;;\nprove) printf '{}' ;;\nesac\n",
+                version, keygen, derive, prove
+            )).unwrap();
+            std::fs::set_permissions(&stub, std::fs::Permissions::from_mode(0o755)).unwrap();
+            stub
+        };
+        let cases = [
+            ("echo 1", "Secret key: aa\\nPublic key: bb\\n", "Public key: bb\\n", "Proof:  cc\\nOutput: dd\\n", CliOutputSchema::V1),
+            ("echo 2", r#"{"secret_key":"aa","public_key":"bb"}"#, r#"{"public_key":"bb"}"#, r#"{"proof":"cc","output":"dd"}"#, CliOutputSchema::V2),
+            // A CLI predating the flag rejects it
+            ("exit 2", "Secret key: aa\\nPublic key: bb\\n", "Public key: bb\\n", "Proof:  cc\\nOutput: dd\\n", CliOutputSchema::V1),
+        ];
+        for (version, keygen, derive, prove, expected) in cases {
+            let stub = stub_cli(version, keygen, derive, prove);
+            let cli = MangekyouCLI::new(Some(stub.to_string_lossy().to_string()));
+            let schema = cli.query_output_schema().unwrap();
+            assert_eq!(schema, expected, "{}", version);
+
+            let proof = cli.with_output_schema(schema).generate_proof("aa", b"seed").unwrap();
+            std::fs::remove_file(&stub).unwrap();
+            assert_eq!((proof.proof.as_str(), proof.output.as_str(), proof.public_key.as_str()), ("cc", "dd", "bb"));
+        }
+
+        let stub = stub_cli("echo 3", "", "", "");
+        let result = MangekyouCLI::new(Some(stub.to_string_lossy().to_string())).query_output_schema();
+        std::fs::remove_file(&stub).unwrap();
+        assert!(matches!(result, Err(CLIError::InvalidOutput(msg)) if msg.contains("Unsupported CLI output format version 3")));
+    }
+
+    #[test]
+    fn test_text_output_parsing_tolerates_formatting_changes() {
+        for stdout in [
+            "Proof:  aa\nOutput: bb\n",
+            "Proof: aa\nOutput: bb",
+            "  Proof:\taa  \r\n\nOutput:   bb\r\n\n",
+            "Output: bb\nProof:  aa\n",
+            "INFO loading secret key\nProof:  aa\n[2024-01-01T00:00:00Z WARN ecvrf] slow RNG: 12ms\nOutput: bb\n",
+            "proof: aa\noutput: bb\n",
+        ] {
+            assert_eq!(CliOutputSchema::V1.parse_proof(stdout).unwrap(), ("aa".to_string(), "bb".to_string(), None), "{:?}", stdout);
+        }
+        assert_eq!(
+            CliOutputSchema::V1.parse_keypair("Generating keypair...\nPublic key:  bb\nSecret key: aa\n\n").unwrap(),
+            ("aa".to_string(), "bb".to_string()),
+        );
+
+        // Missing values still fail, naming what's missing
+        let err = CliOutputSchema::V1.parse_proof("Proof: aa\nOutput:\n").unwrap_err();
+        assert!(matches!(err, CLIError::InvalidOutput(msg) if msg.contains("'Output:'")));
+        assert!(CliOutputSchema::V1.parse_proof("Proof: aa\nOutput: bb\nGamma: cc\n").is_err());
+    }
+
+    #[test]
+    fn test_verbose_proof_components_recombine() {
+        // Known-answer proof from ecvrf-cli
+        let proof = "54b58f527e999ceedb24485a7629e3caa9f7deb152852a0f483a6646495fa253\
+                     c4131e87ff0b48fefacf4b5be04211a7\
+                     7390ca85553aa2c06f0023db34e7b36194eadf11539c0ef1c8dcae09aa35580a";
+        let stdout = format!(
+            "Proof:  {}\nOutput: 00\nGamma: {}\nC: {}\nS: {}\n",
+            proof, &proof[..64], &proof[64..96], &proof[96..]
+        );
+
+        let (parsed, output, components) = CliOutputSchema::V1.parse_proof(&stdout).unwrap();
+        let components = components.expect("verbose output carries components");
+        assert_eq!((parsed.as_str(), output.as_str()), (proof, "00"));
+        assert_eq!(components.gamma_bytes().unwrap().len(), 32);
+        assert_eq!(components.c_bytes().unwrap().len(), 16);
+        assert_eq!(components.s_bytes().unwrap().len(), 32);
+        assert_eq!(hex::encode(components.to_proof_bytes().unwrap()), proof);
+
+        // Components are also derivable from a plain proof
+        let plain = VRFCliProof { proof: proof.to_string(), output, public_key: String::new(), components: None };
+        assert_eq!(plain.proof_components().unwrap(), components);
+        assert!(CliOutputSchema::V1.parse_proof(&format!("Proof:  {}\nOutput: 00\n", proof)).unwrap().2.is_none());
     }
 }
@@ -0,0 +1,96 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::Mutex,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+/// Source of "now", and of timed waits, for age, expiry, latency, timeout and
+/// watchdog logic, so that logic can be driven deterministically in tests
+pub trait Clock: Send + Sync {
+    /// Monotonic time, for measuring durations
+    fn now_instant(&self) -> Instant;
+
+    /// Wall-clock time in seconds since the Unix epoch
+    fn now_unix(&self) -> u64;
+
+    /// Wall-clock time in milliseconds since the Unix epoch
+    fn now_unix_millis(&self) -> u64;
+
+    /// Monotonic time elapsed since `earlier`
+    fn elapsed_since(&self, earlier: Instant) -> Duration {
+        self.now_instant().saturating_duration_since(earlier)
+    }
+
+    /// Wait for `duration` to pass on this clock
+    fn sleep(&self, duration: Duration) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+        Box::pin(tokio::time::sleep(duration))
+    }
+}
+
+/// The real system clock
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_instant(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn now_unix(&self) -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0)
+    }
+
+    fn now_unix_millis(&self) -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0)
+    }
+}
+
+/// A clock that only moves when told to
+#[derive(Debug)]
+pub struct MockClock {
+    /// Monotonic time and Unix time in milliseconds
+    state: Mutex<(Instant, u64)>,
+}
+
+impl MockClock {
+    /// Start at the current instant and the given Unix time
+    pub fn new(unix: u64) -> Self {
+        Self {
+            state: Mutex::new((Instant::now(), unix * 1000)),
+        }
+    }
+
+    /// Move both the monotonic and wall-clock time forward
+    pub fn advance(&self, by: Duration) {
+        let mut state = self.state.lock().unwrap();
+        state.0 += by;
+        state.1 += by.as_millis() as u64;
+    }
+}
+
+impl Clock for MockClock {
+    fn now_instant(&self) -> Instant {
+        self.state.lock().unwrap().0
+    }
+
+    fn now_unix(&self) -> u64 {
+        self.state.lock().unwrap().1 / 1000
+    }
+
+    fn now_unix_millis(&self) -> u64 {
+        self.state.lock().unwrap().1
+    }
+
+    /// Moves the clock forward instead of waiting, so timeouts pass instantly
+    fn sleep(&self, duration: Duration) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+        self.advance(duration);
+        Box::pin(tokio::task::yield_now())
+    }
+}
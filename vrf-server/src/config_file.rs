@@ -0,0 +1,103 @@
+use {
+    crate::enhanced_vrf_server::ServerConfig,
+    serde::Deserialize,
+    solana_sdk::pubkey::Pubkey,
+    std::{collections::HashSet, error::Error, fs, path::Path, str::FromStr, time::Duration},
+};
+
+/// Settings file (JSON) applied on top of the command line at startup and
+/// re-read on SIGHUP.
+///
+/// The typed fields are hot-reloadable; any other key is kept as-is and only
+/// takes effect after a restart.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct ConfigFile {
+    pub poll_interval_ms: Option<u64>,
+    pub max_concurrent_confirms: Option<usize>,
+    pub max_concurrency: Option<usize>,
+    pub proof_concurrency: Option<usize>,
+    pub fulfillment_group_size: Option<usize>,
+    pub in_flight_ttl_secs: Option<u64>,
+    pub min_confirmations: Option<usize>,
+    pub rate_limit_backoff_ms: Option<u64>,
+    pub account_in_use_backoff_ms: Option<u64>,
+    /// Only fulfill requests from these requesters
+    pub requester_allowlist: Option<Vec<String>>,
+    /// Settings that can't change while running
+    #[serde(flatten)]
+    pub restart_only: serde_json::Map<String, serde_json::Value>,
+}
+
+impl ConfigFile {
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let contents = fs::read_to_string(path).map_err(|e| format!("Failed to read config {:?}: {}", path, e))?;
+        Ok(serde_json::from_str(&contents).map_err(|e| format!("Invalid config {:?}: {}", path, e))?)
+    }
+
+    /// Apply the hot-reloadable settings, returning the names of those that changed
+    pub fn apply(&self, config: &mut ServerConfig) -> Result<Vec<&'static str>, Box<dyn Error>> {
+        let mut changed = Vec::new();
+        let mut set = |name: &'static str, changed_value: bool| {
+            if changed_value {
+                changed.push(name);
+            }
+        };
+
+        if let Some(ms) = self.poll_interval_ms {
+            set("poll_interval_ms", replace(&mut config.poll_interval, Duration::from_millis(ms)));
+        }
+        if let Some(limit) = self.max_concurrent_confirms {
+            set("max_concurrent_confirms", replace(&mut config.max_concurrent_confirms, limit));
+        }
+        if let Some(limit) = self.max_concurrency {
+            set("max_concurrency", replace(&mut config.max_concurrency, limit));
+        }
+        if let Some(limit) = self.proof_concurrency {
+            set("proof_concurrency", replace(&mut config.proof_concurrency, limit));
+        }
+        if let Some(size) = self.fulfillment_group_size {
+            set("fulfillment_group_size", replace(&mut config.fulfillment_group_size, size));
+        }
+        if let Some(secs) = self.in_flight_ttl_secs {
+            set("in_flight_ttl_secs", replace(&mut config.in_flight_ttl, Duration::from_secs(secs)));
+        }
+        if let Some(depth) = self.min_confirmations {
+            set("min_confirmations", replace(&mut config.min_confirmations, Some(depth)));
+        }
+        if let Some(ms) = self.rate_limit_backoff_ms {
+            set("rate_limit_backoff_ms", replace(&mut config.rate_limit_backoff, Duration::from_millis(ms)));
+        }
+        if let Some(ms) = self.account_in_use_backoff_ms {
+            set("account_in_use_backoff_ms", replace(&mut config.account_in_use_backoff, Duration::from_millis(ms)));
+        }
+        if let Some(allowlist) = &self.requester_allowlist {
+            let allowlist = allowlist
+                .iter()
+                .map(|requester| Pubkey::from_str(requester).map_err(|e| format!("Invalid requester {}: {}", requester, e)))
+                .collect::<Result<HashSet<_>, _>>()?;
+            set("requester_allowlist", replace(&mut config.requester_allowlist, Some(allowlist)));
+        }
+        Ok(changed)
+    }
+
+    /// Names of restart-only settings that differ from `previous`
+    pub fn restart_required(&self, previous: &ConfigFile) -> Vec<String> {
+        let mut keys: Vec<String> = self.restart_only.keys()
+            .chain(previous.restart_only.keys())
+            .filter(|key| self.restart_only.get(*key) != previous.restart_only.get(*key))
+            .cloned()
+            .collect();
+        keys.sort();
+        keys.dedup();
+        keys
+    }
+}
+
+/// Store `value` in `slot`, returning whether it changed
+fn replace<T: PartialEq>(slot: &mut T, value: T) -> bool {
+    if *slot == value {
+        return false;
+    }
+    *slot = value;
+    true
+}
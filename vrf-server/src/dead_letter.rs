@@ -0,0 +1,60 @@
+use {
+    crate::state_file::{load_json_state, save_json_state, sibling_path},
+    serde::{Deserialize, Serialize},
+    std::{
+        collections::HashMap,
+        error::Error,
+        path::{Path, PathBuf},
+    },
+};
+
+/// Why a request was set aside instead of retried
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeadLetter {
+    /// Unix timestamp of the quarantine
+    pub timestamp: u64,
+    pub reason: String,
+}
+
+/// Requests that can never be fulfilled as they are, kept out of every
+/// sweep until an operator looks at them
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DeadLetterStore {
+    requests: HashMap<String, DeadLetter>,
+}
+
+impl DeadLetterStore {
+    pub fn quarantine(&mut self, request: &str, timestamp: u64, reason: &str) {
+        self.requests.insert(request.to_string(), DeadLetter { timestamp, reason: reason.to_string() });
+    }
+
+    pub fn contains(&self, request: &str) -> bool {
+        self.requests.contains_key(request)
+    }
+
+    pub fn get(&self, request: &str) -> Option<DeadLetter> {
+        self.requests.get(request).cloned()
+    }
+
+    pub fn len(&self) -> usize {
+        self.requests.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.requests.is_empty()
+    }
+
+    pub fn save(&self, path: &Path, compress: bool) -> Result<(), Box<dyn Error>> {
+        save_json_state(path, self, compress)
+    }
+
+    /// Load a saved store; a missing file is an empty store
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        load_json_state(path, "dead-letter store")
+    }
+}
+
+/// Where the dead-letter store of a state file is kept
+pub fn dead_letter_path(state_file: &Path) -> PathBuf {
+    sibling_path(state_file, ".dead-letters")
+}
@@ -0,0 +1,83 @@
+use {
+    crate::state_file::{load_json_state, save_json_state, sibling_path},
+    serde::{Deserialize, Serialize},
+    std::{
+        collections::{HashMap, VecDeque},
+        error::Error,
+        path::{Path, PathBuf},
+    },
+};
+
+/// Failed attempts kept per request; older ones are dropped first
+pub const FAILURE_HISTORY_LIMIT: usize = 16;
+
+/// One failed fulfillment attempt
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FailureRecord {
+    /// Unix timestamp of the attempt
+    pub timestamp: u64,
+    /// 1-based attempt number, counting attempts dropped from the history
+    pub attempt: u32,
+    pub error: String,
+}
+
+/// Bounded per-request log of failed fulfillment attempts, for support triage
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FailureHistory {
+    requests: HashMap<String, VecDeque<FailureRecord>>,
+}
+
+impl FailureHistory {
+    /// Record a failed attempt, returning its attempt number
+    pub fn record(&mut self, request: &str, timestamp: u64, error: &str) -> u32 {
+        let history = self.requests.entry(request.to_string()).or_default();
+        let attempt = history.back().map_or(1, |last| last.attempt + 1);
+        if history.len() == FAILURE_HISTORY_LIMIT {
+            history.pop_front();
+        }
+        history.push_back(FailureRecord { timestamp, attempt, error: error.to_string() });
+        attempt
+    }
+
+    /// Failed attempts of `request`, oldest first
+    pub fn get(&self, request: &str) -> Vec<FailureRecord> {
+        self.requests.get(request).map(|history| history.iter().cloned().collect()).unwrap_or_default()
+    }
+
+    /// Latest failed attempt of `request`
+    pub fn latest(&self, request: &str) -> Option<&FailureRecord> {
+        self.requests.get(request).and_then(|history| history.back())
+    }
+
+    /// Requests with failed attempts
+    pub fn requests(&self) -> impl Iterator<Item = &str> {
+        self.requests.keys().map(String::as_str)
+    }
+
+    /// Forget a request once it is fulfilled
+    pub fn clear(&mut self, request: &str) {
+        self.requests.remove(request);
+    }
+
+    pub fn len(&self) -> usize {
+        self.requests.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.requests.is_empty()
+    }
+
+    pub fn save(&self, path: &Path, compress: bool) -> Result<(), Box<dyn Error>> {
+        save_json_state(path, self, compress)
+    }
+
+    /// Load a saved history; a missing file is an empty history
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        load_json_state(path, "failure history")
+    }
+}
+
+/// Where the failure history of a state file is kept
+pub fn failure_history_path(state_file: &Path) -> PathBuf {
+    sibling_path(state_file, ".failures")
+}
@@ -5,6 +5,25 @@ pub mod event;
 pub mod vrf_server;
 pub mod cli_integration;
 pub mod enhanced_vrf_server;
+pub mod prover;
+pub mod request_filter;
+pub mod metrics;
+pub mod state_file;
+pub mod seed_hook;
+pub mod audit;
+pub mod clock;
+pub mod rpc_pool;
+pub mod proof_cache;
+pub mod webhook;
+pub mod program_errors;
+pub mod config_file;
+pub mod failure_history;
+pub mod dead_letter;
+pub mod seen_seeds;
+pub mod bench;
+pub mod output_monitor;
+pub mod request_source;
+pub mod request_store;
 
 // Re-export the modules
 pub use crate::error::*;
@@ -14,3 +33,22 @@ pub use crate::event::*;
 pub use crate::vrf_server::*;
 pub use crate::cli_integration::*;
 pub use crate::enhanced_vrf_server::*;
+pub use crate::prover::*;
+pub use crate::request_filter::*;
+pub use crate::metrics::*;
+pub use crate::state_file::*;
+pub use crate::seed_hook::*;
+pub use crate::audit::*;
+pub use crate::clock::*;
+pub use crate::rpc_pool::*;
+pub use crate::proof_cache::*;
+pub use crate::webhook::*;
+pub use crate::program_errors::*;
+pub use crate::config_file::*;
+pub use crate::failure_history::*;
+pub use crate::dead_letter::*;
+pub use crate::seen_seeds::*;
+pub use crate::bench::*;
+pub use crate::output_monitor::*;
+pub use crate::request_source::*;
+pub use crate::request_store::*;
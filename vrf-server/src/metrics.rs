@@ -0,0 +1,162 @@
+use {
+    log::{debug, warn},
+    serde::Serialize,
+    std::{
+        fmt::Write as _,
+        sync::{
+            atomic::{AtomicBool, AtomicU64, Ordering},
+            Arc,
+        },
+    },
+    tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::{TcpListener, TcpStream},
+    },
+};
+
+/// Largest request head read from a scraper; anything longer is cut off
+const MAX_REQUEST_HEAD: usize = 8 * 1024;
+
+/// Bucket upper bounds (ms) for the send-to-confirmed latency histogram
+pub const CONFIRMATION_LATENCY_BUCKETS_MS: &[u64] = &[250, 500, 1_000, 2_000, 5_000, 10_000, 30_000, 60_000];
+
+/// Bucket upper bounds (bytes) for the VRF input size histogram
+pub const SEED_SIZE_BUCKETS: &[u64] = &[8, 16, 32, 64, 128, 256, 512, 1_024];
+
+/// Bucket upper bounds (bytes) for the encoded proof size histogram
+pub const PROOF_SIZE_BUCKETS: &[u64] = &[64, 80, 96, 128, 256];
+
+/// Fixed-bucket histogram of `u64` observations
+#[derive(Debug, Clone, Serialize)]
+pub struct Histogram {
+    /// Inclusive upper bound of each bucket
+    pub bounds: Vec<u64>,
+    /// Observations per bucket; the extra last entry counts values above every bound
+    pub counts: Vec<u64>,
+    /// Sum of all observations
+    pub sum: u64,
+    /// Number of observations
+    pub count: u64,
+}
+
+impl Histogram {
+    pub fn new(bounds: &[u64]) -> Self {
+        Self {
+            bounds: bounds.to_vec(),
+            counts: vec![0; bounds.len() + 1],
+            sum: 0,
+            count: 0,
+        }
+    }
+
+    /// Index of the bucket `value` falls into
+    pub fn bucket_for(&self, value: u64) -> usize {
+        self.bounds.iter().position(|&bound| value <= bound).unwrap_or(self.bounds.len())
+    }
+
+    pub fn record(&mut self, value: u64) {
+        let bucket = self.bucket_for(value);
+        self.counts[bucket] += 1;
+        self.sum = self.sum.saturating_add(value);
+        self.count += 1;
+    }
+}
+
+/// Counters and gauges served on the `/metrics` endpoint
+#[derive(Debug, Default)]
+pub struct ServerMetrics {
+    /// Requests fulfilled successfully
+    pub requests_processed: AtomicU64,
+    /// Fulfillment attempts that failed, for any reason
+    pub fulfillment_failures: AtomicU64,
+    /// Failures to generate or verify a proof
+    pub proof_failures: AtomicU64,
+    /// Pending requests seen in the last scan
+    pub pending_requests: AtomicU64,
+    /// Set once the prover is built and the keys are loaded
+    ready: AtomicBool,
+}
+
+impl ServerMetrics {
+    pub fn mark_ready(&self) {
+        self.ready.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.ready.load(Ordering::SeqCst)
+    }
+
+    /// Render in the Prometheus text exposition format
+    pub fn render(&self) -> String {
+        let mut text = String::new();
+        for (name, kind, help, value) in [
+            ("kamui_vrf_requests_processed_total", "counter", "VRF requests fulfilled", &self.requests_processed),
+            ("kamui_vrf_fulfillment_failures_total", "counter", "Failed fulfillment attempts", &self.fulfillment_failures),
+            ("kamui_vrf_proof_failures_total", "counter", "Failed proof generations or verifications", &self.proof_failures),
+            ("kamui_vrf_pending_requests", "gauge", "Pending requests seen in the last scan", &self.pending_requests),
+        ] {
+            let _ = writeln!(text, "# HELP {} {}", name, help);
+            let _ = writeln!(text, "# TYPE {} {}", name, kind);
+            let _ = writeln!(text, "{} {}", name, value.load(Ordering::SeqCst));
+        }
+        text
+    }
+
+    /// Status code, content type and body answering a GET of `path`
+    pub fn respond(&self, path: &str) -> (u16, &'static str, String) {
+        match path {
+            "/metrics" => (200, "text/plain; version=0.0.4", self.render()),
+            "/healthz" if self.is_ready() => (200, "text/plain", "ok\n".to_string()),
+            "/healthz" => (503, "text/plain", "starting\n".to_string()),
+            _ => (404, "text/plain", "not found\n".to_string()),
+        }
+    }
+}
+
+/// Serve `/metrics` and `/healthz` over plain HTTP until the task is dropped
+pub async fn serve_metrics(listener: TcpListener, metrics: Arc<ServerMetrics>) {
+    loop {
+        match listener.accept().await {
+            Ok((stream, peer)) => {
+                let metrics = metrics.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = answer(stream, &metrics).await {
+                        debug!("Metrics request from {} failed: {}", peer, e);
+                    }
+                });
+            }
+            Err(e) => warn!("⚠️  Failed to accept metrics connection: {}", e),
+        }
+    }
+}
+
+/// Answer a single request and close the connection
+async fn answer(mut stream: TcpStream, metrics: &ServerMetrics) -> std::io::Result<()> {
+    let mut head = Vec::new();
+    let mut buf = [0u8; 1024];
+    while !head.windows(4).any(|window| window == b"\r\n\r\n") && head.len() < MAX_REQUEST_HEAD {
+        let read = stream.read(&mut buf).await?;
+        if read == 0 {
+            break;
+        }
+        head.extend_from_slice(&buf[..read]);
+    }
+    let head = String::from_utf8_lossy(&head);
+    let mut request_line = head.lines().next().unwrap_or_default().split_whitespace();
+    let (status, content_type, body) = match (request_line.next(), request_line.next()) {
+        (Some("GET"), Some(path)) => metrics.respond(path.split('?').next().unwrap_or(path)),
+        _ => (405, "text/plain", "method not allowed\n".to_string()),
+    };
+    let reason = match status {
+        200 => "OK",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        _ => "Service Unavailable",
+    };
+    let response = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status, reason, content_type, body.len(), body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
@@ -0,0 +1,44 @@
+use std::collections::{HashMap, VecDeque};
+
+/// Number of recent VRF outputs remembered by default
+pub const REPEATED_OUTPUT_WINDOW: usize = 4_096;
+
+/// Remembers recent VRF outputs to catch the same output for distinct inputs.
+///
+/// A working VRF never does that, so a repeat means the prover is emitting a
+/// constant or recycled value (bad key, RNG or CLI regression).
+#[derive(Debug)]
+pub struct RepeatedOutputDetector {
+    window: usize,
+    inputs: HashMap<String, Vec<u8>>,
+    order: VecDeque<String>,
+}
+
+impl RepeatedOutputDetector {
+    pub fn new(window: usize) -> Self {
+        Self { window: window.max(1), inputs: HashMap::new(), order: VecDeque::new() }
+    }
+
+    /// Record `output` for `input`, returning the earlier, different input
+    /// that produced the same output, if any
+    pub fn observe(&mut self, output: &str, input: &[u8]) -> Option<Vec<u8>> {
+        if let Some(previous) = self.inputs.get(output) {
+            // Re-proving the same input (a retry or a cached proof) is expected
+            return (previous.as_slice() != input).then(|| previous.clone());
+        }
+        if self.order.len() == self.window {
+            if let Some(oldest) = self.order.pop_front() {
+                self.inputs.remove(&oldest);
+            }
+        }
+        self.order.push_back(output.to_string());
+        self.inputs.insert(output.to_string(), input.to_vec());
+        None
+    }
+}
+
+impl Default for RepeatedOutputDetector {
+    fn default() -> Self {
+        Self::new(REPEATED_OUTPUT_WINDOW)
+    }
+}
@@ -15,7 +15,7 @@ use {
         program_error::ProgramError,
         pubkey::Pubkey,
         system_instruction,
-        sysvar::{rent::Rent, Sysvar},
+        sysvar::{clock::Clock, rent::Rent, Sysvar},
     },
 };
 use spl_token::instruction as token_instruction;
@@ -107,6 +107,7 @@ impl Processor {
             min_balance,
             confirmations,
             nonce: 0,
+            authorized_oracles: None,
         };
 
         let rent = Rent::get()?;
@@ -253,12 +254,13 @@ impl Processor {
                 requester: *requester.key,
                 seed,
                 callback_data,
-                request_block: 0, // Will be set by runtime
+                request_block: Clock::get()?.slot,
                 status: RequestStatus::Pending,
                 num_words,
                 callback_gas_limit,
                 nonce: subscription.nonce,
                 commitment: [0; 32],
+                assigned_oracle: None,
             };
 
             let space = borsh::to_vec(&request)?.len() + 8;  // Add 8 bytes for discriminator
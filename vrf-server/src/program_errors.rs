@@ -0,0 +1,161 @@
+use {
+    crate::error::VrfCoordinatorError,
+    serde::Deserialize,
+    std::{collections::HashMap, error::Error, fs, path::Path},
+};
+
+/// Human-readable name of a program `Custom(u32)` error
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProgramErrorName {
+    pub name: String,
+    pub msg: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct IdlError {
+    code: u32,
+    name: String,
+    msg: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Idl {
+    #[serde(default)]
+    errors: Vec<IdlError>,
+}
+
+/// Translates program custom error codes into their names.
+///
+/// Starts out knowing the native coordinator's `VrfCoordinatorError` codes;
+/// more can be loaded from an Anchor IDL's `errors` section.
+#[derive(Debug, Clone)]
+pub struct ProgramErrorCodes {
+    names: HashMap<u32, ProgramErrorName>,
+}
+
+impl Default for ProgramErrorCodes {
+    fn default() -> Self {
+        use VrfCoordinatorError::*;
+
+        let mut codes = Self { names: HashMap::new() };
+        for error in [
+            InvalidInstruction,
+            NotRentExempt,
+            InsufficientBalance,
+            InvalidSubscriptionOwner,
+            InvalidRequestStatus,
+            InvalidOracleSigner,
+            InvalidVrfProof,
+            RequestAlreadyFulfilled,
+            InsufficientConfirmations,
+            InvalidRequestConfirmations,
+            InvalidCallbackGasLimit,
+            InvalidNumberOfWords,
+            InvalidOracle,
+            InvalidCommitment,
+            CallbackFailed,
+            RequestExpired,
+            InvalidRequestParameters,
+        ] {
+            codes.insert(error as u32, format!("{:?}", error), Some(error.to_string()));
+        }
+        codes
+    }
+}
+
+impl ProgramErrorCodes {
+    pub fn insert(&mut self, code: u32, name: impl Into<String>, msg: Option<String>) {
+        self.names.insert(code, ProgramErrorName { name: name.into(), msg });
+    }
+
+    /// Add the errors declared in an Anchor IDL (JSON), overriding known codes
+    pub fn extend_from_idl(&mut self, idl_json: &str) -> Result<usize, String> {
+        let idl: Idl = serde_json::from_str(idl_json).map_err(|e| format!("Invalid IDL: {}", e))?;
+        let count = idl.errors.len();
+        for error in idl.errors {
+            self.insert(error.code, error.name, error.msg);
+        }
+        Ok(count)
+    }
+
+    /// Add the errors declared in the IDL file at `path`
+    pub fn load_idl(&mut self, path: &Path) -> Result<usize, Box<dyn Error>> {
+        let idl_json = fs::read_to_string(path).map_err(|e| format!("Failed to read IDL {:?}: {}", path, e))?;
+        Ok(self.extend_from_idl(&idl_json)?)
+    }
+
+    pub fn lookup(&self, code: u32) -> Option<&ProgramErrorName> {
+        self.names.get(&code)
+    }
+
+    fn describe(&self, code: u32) -> Option<String> {
+        self.lookup(code).map(|known| match &known.msg {
+            Some(msg) => format!("{}: {}", known.name, msg),
+            None => known.name.clone(),
+        })
+    }
+
+    /// Append the name of every known custom error code in an error message.
+    ///
+    /// Recognizes both the `custom program error: 0x1771` display form and
+    /// the `Custom(6001)` debug form.
+    pub fn annotate(&self, message: &str) -> String {
+        let mut annotated = String::with_capacity(message.len());
+        let mut rest = message;
+        while let Some((start, prefix, radix)) = [("custom program error: 0x", 16), ("Custom(", 10)]
+            .iter()
+            .filter_map(|&(prefix, radix)| rest.find(prefix).map(|start| (start, prefix, radix)))
+            .min_by_key(|&(start, _, _)| start)
+        {
+            let digits_start = start + prefix.len();
+            let digits_len = rest[digits_start..]
+                .find(|c: char| !c.is_digit(radix))
+                .unwrap_or(rest.len() - digits_start);
+            let end = digits_start + digits_len;
+            // The debug form closes with a parenthesis that belongs to the match
+            let end = if radix == 10 && rest[end..].starts_with(')') { end + 1 } else { end };
+            annotated.push_str(&rest[..end]);
+
+            let code = u32::from_str_radix(&rest[digits_start..digits_start + digits_len], radix).ok();
+            if let Some(description) = code.and_then(|code| self.describe(code)) {
+                annotated.push_str(&format!(" [{}]", description));
+            }
+            rest = &rest[end..];
+        }
+        annotated.push_str(rest);
+        annotated
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        solana_client::client_error::{ClientError, ClientErrorKind},
+        solana_sdk::{instruction::InstructionError, transaction::TransactionError},
+    };
+
+    #[test]
+    fn test_custom_error_code_is_named() {
+        let mut codes = ProgramErrorCodes::default();
+        let loaded = codes
+            .extend_from_idl(r#"{"errors": [{"code": 6001, "name": "RequestAlreadyFulfilled", "msg": "Request is already fulfilled"}]}"#)
+            .unwrap();
+        assert_eq!(loaded, 1);
+
+        let error: ClientError =
+            ClientErrorKind::TransactionError(TransactionError::InstructionError(0, InstructionError::Custom(6001))).into();
+        let annotated = codes.annotate(&error.to_string());
+        assert!(annotated.contains("0x1771 [RequestAlreadyFulfilled: Request is already fulfilled]"), "{}", annotated);
+
+        let annotated = codes.annotate("Simulation failed: InstructionError(0, Custom(6001))");
+        assert_eq!(
+            annotated,
+            "Simulation failed: InstructionError(0, Custom(6001) [RequestAlreadyFulfilled: Request is already fulfilled])"
+        );
+
+        // Built-in native coordinator codes, and unknown codes left untouched
+        assert!(codes.annotate("Custom(7)").contains("RequestAlreadyFulfilled"));
+        assert_eq!(codes.annotate("Custom(424242)"), "Custom(424242)");
+    }
+}
@@ -0,0 +1,107 @@
+use {
+    crate::{cli_integration::VRFCliProof, state_file::save_json_state},
+    log::{debug, warn},
+    serde::{Deserialize, Serialize},
+    sha2::{Digest, Sha256},
+    solana_sdk::pubkey::Pubkey,
+    std::{
+        error::Error,
+        fs,
+        path::{Path, PathBuf},
+    },
+};
+
+/// On-disk cache entry; the key material is stored alongside the proof so a
+/// hash collision or a stale file can never serve a proof for another key
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedProof {
+    vrf_public_key: String,
+    request: String,
+    input_hash: String,
+    proof: VRFCliProof,
+}
+
+/// Directory of generated proofs keyed by `(vrf_public_key, request_pubkey, input_hash)`.
+///
+/// The input is what the prover was given: the seed after decoding and any
+/// seed hook. The VRF is deterministic, so a cached proof is exactly what the
+/// prover would produce again for that input. Entries for a different VRF key are never matched,
+/// which invalidates the whole cache when the key changes.
+#[derive(Debug, Clone)]
+pub struct ProofCache {
+    dir: PathBuf,
+}
+
+impl ProofCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn entry_path(&self, vrf_public_key: &str, request: &Pubkey, input: &[u8]) -> (PathBuf, String) {
+        let input_hash = hex::encode(Sha256::digest(input));
+        let mut hasher = Sha256::new();
+        hasher.update(vrf_public_key.as_bytes());
+        hasher.update(request.as_ref());
+        hasher.update(input_hash.as_bytes());
+        (self.dir.join(format!("{}.json", hex::encode(hasher.finalize()))), input_hash)
+    }
+
+    /// Cached proof for this key, request and VRF input, if any
+    pub fn get(&self, vrf_public_key: &str, request: &Pubkey, input: &[u8]) -> Option<VRFCliProof> {
+        let (path, input_hash) = self.entry_path(vrf_public_key, request, input);
+        let bytes = fs::read(&path).ok()?;
+        match serde_json::from_slice::<CachedProof>(&bytes) {
+            Ok(entry)
+                if entry.vrf_public_key == vrf_public_key
+                    && entry.proof.public_key == vrf_public_key
+                    && entry.request == request.to_string()
+                    && entry.input_hash == input_hash =>
+            {
+                debug!("Proof cache hit for request {}", request);
+                Some(entry.proof)
+            }
+            Ok(_) => {
+                warn!("⚠️  Ignoring proof cache entry {:?} for a different key or request", path);
+                None
+            }
+            Err(e) => {
+                warn!("⚠️  Ignoring unreadable proof cache entry {:?}: {}", path, e);
+                None
+            }
+        }
+    }
+
+    /// Store a proof generated for this key, request and VRF input
+    pub fn put(
+        &self,
+        vrf_public_key: &str,
+        request: &Pubkey,
+        input: &[u8],
+        proof: &VRFCliProof,
+    ) -> Result<(), Box<dyn Error>> {
+        fs::create_dir_all(&self.dir)
+            .map_err(|e| format!("Failed to create proof cache {:?}: {}", self.dir, e))?;
+        let (path, input_hash) = self.entry_path(vrf_public_key, request, input);
+        let entry = CachedProof {
+            vrf_public_key: vrf_public_key.to_string(),
+            request: request.to_string(),
+            input_hash,
+            proof: proof.clone(),
+        };
+        save_json_state(&path, &entry, false)
+    }
+
+    /// Drop the proof for this key, request and VRF input, so it's proved afresh
+    pub fn remove(&self, vrf_public_key: &str, request: &Pubkey, input: &[u8]) {
+        let (path, _) = self.entry_path(vrf_public_key, request, input);
+        match fs::remove_file(&path) {
+            Ok(()) => debug!("Evicted proof cache entry for request {}", request),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => warn!("⚠️  Failed to evict proof cache entry {:?}: {}", path, e),
+        }
+    }
+}
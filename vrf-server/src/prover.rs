@@ -0,0 +1,293 @@
+use {
+    crate::cli_integration::{CLIError, MangekyouCLI, VRFCliProof},
+    log::{info, warn},
+    mangekyou::kamui_vrf::{
+        ecvrf::{ECVRFKeyPair, ECVRFPrivateKey, ECVRFProof, ECVRFPublicKey},
+        VRFKeyPair, VRFProof,
+    },
+    std::{str::FromStr, sync::Arc},
+};
+
+/// Length of an encoded ECVRF proof (gamma || c || s)
+pub const ECVRF_PROOF_LEN: usize = 80;
+
+/// Scheme identifier of the mangekyou ECVRF suite, as reported by `ecvrf-cli scheme`
+pub const ECVRF_SCHEME: &str = "ECVRF-RISTRETTO255-SHA512/sol_vrf";
+
+/// A backend able to generate and verify ECVRF proofs.
+///
+/// Keys, proofs and outputs are exchanged hex encoded, matching the
+/// `ecvrf-cli` interface so backends are interchangeable.
+pub trait VrfProver: Send + Sync {
+    /// Short identifier used in logs and stats
+    fn name(&self) -> &'static str;
+
+    /// VRF scheme (curve, hash suite) this backend proves with
+    fn scheme(&self) -> Result<String, CLIError>;
+
+    /// Generate a new VRF keypair as (secret_key, public_key)
+    fn generate_keypair(&self) -> Result<(String, String), CLIError>;
+
+    /// Public key matching `secret_key`
+    fn derive_public_key(&self, secret_key: &str) -> Result<String, CLIError> {
+        InProcessProver::keypair_from_secret(secret_key).map(|keypair| hex::encode(&keypair.pk))
+    }
+
+    /// Generate a VRF proof for `input` with the given secret key
+    fn generate_proof(&self, secret_key: &str, input: &[u8]) -> Result<VRFCliProof, CLIError>;
+
+    /// Verify a VRF proof and output against a public key and input
+    fn verify_proof(
+        &self,
+        proof: &str,
+        output: &str,
+        public_key: &str,
+        input: &[u8],
+    ) -> Result<bool, CLIError>;
+}
+
+impl VrfProver for MangekyouCLI {
+    fn name(&self) -> &'static str {
+        "cli"
+    }
+
+    fn scheme(&self) -> Result<String, CLIError> {
+        MangekyouCLI::scheme(self)
+    }
+
+    fn generate_keypair(&self) -> Result<(String, String), CLIError> {
+        MangekyouCLI::generate_keypair(self)
+    }
+
+    fn derive_public_key(&self, secret_key: &str) -> Result<String, CLIError> {
+        MangekyouCLI::derive_public_key(self, secret_key)
+    }
+
+    fn generate_proof(&self, secret_key: &str, input: &[u8]) -> Result<VRFCliProof, CLIError> {
+        MangekyouCLI::generate_proof(self, secret_key, input)
+    }
+
+    fn verify_proof(
+        &self,
+        proof: &str,
+        output: &str,
+        public_key: &str,
+        input: &[u8],
+    ) -> Result<bool, CLIError> {
+        MangekyouCLI::verify_proof(self, proof, output, public_key, input)
+    }
+}
+
+/// Proves in-process by linking the mangekyou ECVRF implementation directly
+#[derive(Debug, Default, Clone, Copy)]
+pub struct InProcessProver;
+
+fn decode_hex(value: &str, what: &str) -> Result<Vec<u8>, CLIError> {
+    hex::decode(value).map_err(|e| CLIError::InvalidOutput(format!("Invalid {} hex: {}", what, e)))
+}
+
+impl InProcessProver {
+    fn keypair_from_secret(secret_key: &str) -> Result<ECVRFKeyPair, CLIError> {
+        let secret_bytes = decode_hex(secret_key, "secret key")?;
+        let secret = ECVRFPrivateKey::from_bytes(&secret_bytes)
+            .map_err(|e| CLIError::InvalidOutput(format!("Invalid secret key: {}", e)))?;
+        Ok(ECVRFKeyPair::from(secret))
+    }
+}
+
+impl VrfProver for InProcessProver {
+    fn name(&self) -> &'static str {
+        "in-process"
+    }
+
+    fn scheme(&self) -> Result<String, CLIError> {
+        Ok(ECVRF_SCHEME.to_string())
+    }
+
+    fn generate_keypair(&self) -> Result<(String, String), CLIError> {
+        let keypair = ECVRFKeyPair::generate(&mut rand::thread_rng());
+        Ok((hex::encode(&keypair.sk), hex::encode(&keypair.pk)))
+    }
+
+    fn generate_proof(&self, secret_key: &str, input: &[u8]) -> Result<VRFCliProof, CLIError> {
+        let keypair = Self::keypair_from_secret(secret_key)?;
+        let proof = keypair.prove(input);
+        Ok(VRFCliProof {
+            proof: hex::encode(proof.to_bytes()),
+            output: hex::encode(proof.to_hash()),
+            public_key: hex::encode(&keypair.pk),
+            components: None,
+        })
+    }
+
+    fn verify_proof(
+        &self,
+        proof: &str,
+        output: &str,
+        public_key: &str,
+        input: &[u8],
+    ) -> Result<bool, CLIError> {
+        let proof_bytes = decode_hex(proof, "proof")?;
+        if proof_bytes.len() != ECVRF_PROOF_LEN {
+            return Ok(false);
+        }
+        let public_key = ECVRFPublicKey::from_bytes(&decode_hex(public_key, "public key")?)
+            .map_err(|e| CLIError::InvalidOutput(format!("Invalid public key: {}", e)))?;
+        let output: [u8; 64] = decode_hex(output, "output")?
+            .try_into()
+            .map_err(|_| CLIError::InvalidOutput("Output must be 64 bytes".to_string()))?;
+        let proof = ECVRFProof::from_bytes(&proof_bytes)
+            .map_err(|e| CLIError::InvalidOutput(format!("Invalid proof: {}", e)))?;
+
+        Ok(proof.verify_output(input, &public_key, &output).is_ok())
+    }
+}
+
+/// Tries the primary backend first and falls back to the secondary on error
+pub struct FallbackProver {
+    primary: Arc<dyn VrfProver>,
+    fallback: Arc<dyn VrfProver>,
+}
+
+impl FallbackProver {
+    pub fn new(primary: Arc<dyn VrfProver>, fallback: Arc<dyn VrfProver>) -> Self {
+        Self { primary, fallback }
+    }
+}
+
+impl VrfProver for FallbackProver {
+    fn name(&self) -> &'static str {
+        "cli-with-inprocess-fallback"
+    }
+
+    fn scheme(&self) -> Result<String, CLIError> {
+        self.primary.scheme().or_else(|e| {
+            warn!("{} scheme query failed, falling back to {}: {}", self.primary.name(), self.fallback.name(), e);
+            self.fallback.scheme()
+        })
+    }
+
+    fn generate_keypair(&self) -> Result<(String, String), CLIError> {
+        self.primary.generate_keypair().or_else(|e| {
+            warn!("{} keygen failed, falling back to {}: {}", self.primary.name(), self.fallback.name(), e);
+            self.fallback.generate_keypair()
+        })
+    }
+
+    fn derive_public_key(&self, secret_key: &str) -> Result<String, CLIError> {
+        self.primary.derive_public_key(secret_key).or_else(|e| {
+            warn!("{} key derivation failed, falling back to {}: {}", self.primary.name(), self.fallback.name(), e);
+            self.fallback.derive_public_key(secret_key)
+        })
+    }
+
+    fn generate_proof(&self, secret_key: &str, input: &[u8]) -> Result<VRFCliProof, CLIError> {
+        self.primary.generate_proof(secret_key, input).or_else(|e| {
+            warn!("{} proof generation failed, falling back to {}: {}", self.primary.name(), self.fallback.name(), e);
+            self.fallback.generate_proof(secret_key, input)
+        })
+    }
+
+    fn verify_proof(
+        &self,
+        proof: &str,
+        output: &str,
+        public_key: &str,
+        input: &[u8],
+    ) -> Result<bool, CLIError> {
+        self.primary.verify_proof(proof, output, public_key, input).or_else(|e| {
+            warn!("{} verification failed, falling back to {}: {}", self.primary.name(), self.fallback.name(), e);
+            self.fallback.verify_proof(proof, output, public_key, input)
+        })
+    }
+}
+
+/// Proof-generation strategy selected at startup
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VrfBackend {
+    /// Shell out to `ecvrf-cli` for every operation
+    #[default]
+    Cli,
+    /// Link mangekyou and prove in-process
+    InProcess,
+    /// Use the CLI, retrying in-process when the CLI fails
+    CliWithInProcessFallback,
+}
+
+impl FromStr for VrfBackend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "cli" => Ok(VrfBackend::Cli),
+            "in-process" => Ok(VrfBackend::InProcess),
+            "cli-with-inprocess-fallback" => Ok(VrfBackend::CliWithInProcessFallback),
+            other => Err(format!(
+                "unknown VRF backend '{}', expected cli, in-process or cli-with-inprocess-fallback",
+                other
+            )),
+        }
+    }
+}
+
+impl VrfBackend {
+    /// Whether this strategy needs the `ecvrf-cli` binary
+    pub fn uses_cli(&self) -> bool {
+        !matches!(self, VrfBackend::InProcess)
+    }
+
+    /// Build the prover for this strategy
+    pub fn build(&self, cli_path: Option<String>) -> Arc<dyn VrfProver> {
+        self.build_with_env(cli_path, Vec::new())
+    }
+
+    /// Build the prover, setting `cli_env` on every CLI invocation
+    pub fn build_with_env(&self, cli_path: Option<String>, cli_env: Vec<(String, String)>) -> Arc<dyn VrfProver> {
+        self.build_with_cli(MangekyouCLI::new(cli_path).with_env(cli_env))
+    }
+
+    /// Build the prover around an already configured CLI
+    pub fn build_with_cli(&self, cli: MangekyouCLI) -> Arc<dyn VrfProver> {
+        info!("Using VRF backend: {:?}", self);
+        match self {
+            VrfBackend::Cli => Arc::new(cli),
+            VrfBackend::InProcess => Arc::new(InProcessProver),
+            VrfBackend::CliWithInProcessFallback => Arc::new(FallbackProver::new(
+                Arc::new(cli),
+                Arc::new(InProcessProver),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MISSING_CLI: &str = "/nonexistent/ecvrf-cli";
+
+    #[test]
+    fn test_in_process_roundtrip() {
+        let prover = InProcessProver;
+        let (secret_key, public_key) = prover.generate_keypair().unwrap();
+        let proof = prover.generate_proof(&secret_key, b"test input").unwrap();
+        assert_eq!(proof.public_key, public_key);
+        assert!(prover.verify_proof(&proof.proof, &proof.output, &proof.public_key, b"test input").unwrap());
+        assert!(!prover.verify_proof(&proof.proof, &proof.output, &proof.public_key, b"other input").unwrap());
+    }
+
+    #[test]
+    fn test_fallback_recovers_when_cli_fails() {
+        let (secret_key, _) = InProcessProver.generate_keypair().unwrap();
+
+        let cli_only = VrfBackend::Cli.build(Some(MISSING_CLI.to_string()));
+        assert!(cli_only.generate_proof(&secret_key, b"seed").is_err());
+
+        let backend: VrfBackend = "cli-with-inprocess-fallback".parse().unwrap();
+        let prover = backend.build(Some(MISSING_CLI.to_string()));
+        let proof = prover.generate_proof(&secret_key, b"seed").expect("fallback should recover");
+        assert!(InProcessProver
+            .verify_proof(&proof.proof, &proof.output, &proof.public_key, b"seed")
+            .unwrap());
+    }
+}
@@ -0,0 +1,29 @@
+use crate::state::RandomnessRequest;
+
+/// Hook for integrator-specific rules deciding which requests get served
+/// (KYC'd requesters, whitelisted callbacks, minimum fees, ...).
+///
+/// Consulted for every pending request before a proof is generated; rejected
+/// requests are reported as skipped and left untouched on-chain.
+pub trait RequestFilter: Send + Sync {
+    fn should_fulfill(&self, request: &RandomnessRequest) -> bool;
+}
+
+/// Default filter that serves every request
+#[derive(Debug, Default, Clone, Copy)]
+pub struct AcceptAll;
+
+impl RequestFilter for AcceptAll {
+    fn should_fulfill(&self, _request: &RandomnessRequest) -> bool {
+        true
+    }
+}
+
+impl<F> RequestFilter for F
+where
+    F: Fn(&RandomnessRequest) -> bool + Send + Sync,
+{
+    fn should_fulfill(&self, request: &RandomnessRequest) -> bool {
+        self(request)
+    }
+}
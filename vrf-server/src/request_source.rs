@@ -0,0 +1,189 @@
+use {
+    crate::{
+        cli_integration::VRFCliProof,
+        enhanced_vrf_server::{classify_request_account, RequestAccountKind},
+    },
+    log::warn,
+    serde::{Deserialize, Serialize},
+    solana_sdk::{account::Account, pubkey::Pubkey},
+    std::{
+        collections::VecDeque,
+        error::Error,
+        sync::{Arc, Mutex},
+    },
+};
+
+/// A request for randomness, wherever it was discovered
+#[derive(Debug, Clone)]
+pub struct VrfRequest {
+    /// Request account for on-chain requests, the producer's id for queued ones
+    pub id: String,
+    /// Seed bytes, decoded into the VRF input like on-chain seeds
+    pub seed: Vec<u8>,
+    pub origin: RequestOrigin,
+}
+
+/// Where a request came from, which decides how its result is delivered
+#[derive(Debug, Clone)]
+pub enum RequestOrigin {
+    /// A request account, fulfilled on-chain by the usual transaction
+    OnChain(Pubkey, Account),
+    /// A request taken from a queue; its proof is delivered back through the source
+    Queue,
+}
+
+/// Discovery of requests to serve, decoupled from how they're found.
+///
+/// The server drains a source until `next_request` returns `None`, then asks
+/// again on the next pass.
+pub trait RequestSource: Send {
+    /// Next request waiting to be served, if any
+    fn next_request(&mut self) -> Option<VrfRequest>;
+
+    /// Hand the proof (or the reason there is none) for request `id` back to
+    /// its producer
+    fn deliver(&mut self, id: &str, result: Result<&VRFCliProof, &str>) -> Result<(), Box<dyn Error>>;
+}
+
+/// The program's request accounts fetched for one pass, built by
+/// `EnhancedVRFServer::on_chain_source` so the fetch goes through its RPC pool.
+///
+/// Every fetched account is handed over; the server's triage decides which
+/// ones are pending and ours to fulfill.
+pub struct OnChainSource {
+    accounts: VecDeque<(Pubkey, Account)>,
+}
+
+impl OnChainSource {
+    pub fn new(accounts: Vec<(Pubkey, Account)>) -> Self {
+        Self { accounts: accounts.into() }
+    }
+}
+
+impl RequestSource for OnChainSource {
+    fn next_request(&mut self) -> Option<VrfRequest> {
+        let (pubkey, account) = self.accounts.pop_front()?;
+        let seed = match classify_request_account(&account.data) {
+            Ok(RequestAccountKind::Request(request)) => request.seed.to_vec(),
+            _ => Vec::new(),
+        };
+        Some(VrfRequest { id: pubkey.to_string(), seed, origin: RequestOrigin::OnChain(pubkey, account) })
+    }
+
+    /// On-chain results land with the fulfillment transaction itself
+    fn deliver(&mut self, _id: &str, _result: Result<&VRFCliProof, &str>) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+}
+
+/// Transport for queued requests and results (a NATS subject, a Redis list,
+/// a Kafka topic, ...); adapters for a concrete broker implement this
+pub trait MessageQueue: Send {
+    /// Next request message, without waiting when the queue is empty
+    fn receive(&mut self) -> Option<Vec<u8>>;
+
+    /// Publish a result message
+    fn publish(&mut self, message: Vec<u8>) -> Result<(), Box<dyn Error>>;
+}
+
+/// Request message consumed from a queue
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueuedRequest {
+    pub id: String,
+    /// Hex-encoded seed
+    pub seed: String,
+}
+
+/// Result message published back to a queue
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueuedResult {
+    pub id: String,
+    pub output: Option<String>,
+    pub proof: Option<String>,
+    pub public_key: Option<String>,
+    pub error: Option<String>,
+}
+
+/// Requests consumed from a message queue, as JSON `QueuedRequest`s, with
+/// results published back as JSON `QueuedResult`s
+pub struct QueueSource<Q> {
+    queue: Q,
+}
+
+impl<Q: MessageQueue> QueueSource<Q> {
+    pub fn new(queue: Q) -> Self {
+        Self { queue }
+    }
+}
+
+impl<Q: MessageQueue> RequestSource for QueueSource<Q> {
+    fn next_request(&mut self) -> Option<VrfRequest> {
+        loop {
+            let message = self.queue.receive()?;
+            let request = serde_json::from_slice::<QueuedRequest>(&message)
+                .map_err(|e| e.to_string())
+                .and_then(|request| Ok((hex::decode(&request.seed).map_err(|e| e.to_string())?, request.id)));
+            match request {
+                Ok((seed, id)) => return Some(VrfRequest { id, seed, origin: RequestOrigin::Queue }),
+                Err(e) => warn!("⚠️  Dropping malformed queued request: {}", e),
+            }
+        }
+    }
+
+    fn deliver(&mut self, id: &str, result: Result<&VRFCliProof, &str>) -> Result<(), Box<dyn Error>> {
+        let message = match result {
+            Ok(proof) => QueuedResult {
+                id: id.to_string(),
+                output: Some(proof.output.clone()),
+                proof: Some(proof.proof.clone()),
+                public_key: Some(proof.public_key.clone()),
+                error: None,
+            },
+            Err(error) => QueuedResult {
+                id: id.to_string(),
+                output: None,
+                proof: None,
+                public_key: None,
+                error: Some(error.to_string()),
+            },
+        };
+        self.queue.publish(serde_json::to_vec(&message)?)
+    }
+}
+
+/// Queue kept in memory, for embedding the oracle in-process and for tests.
+/// Clones share the same queues.
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryQueue {
+    requests: Arc<Mutex<VecDeque<Vec<u8>>>>,
+    results: Arc<Mutex<Vec<Vec<u8>>>>,
+}
+
+impl InMemoryQueue {
+    /// Enqueue a raw request message
+    pub fn push(&self, message: Vec<u8>) {
+        self.requests.lock().unwrap().push_back(message);
+    }
+
+    /// Enqueue a request for `seed`
+    pub fn push_request(&self, id: &str, seed: &[u8]) {
+        let request = QueuedRequest { id: id.to_string(), seed: hex::encode(seed) };
+        self.push(serde_json::to_vec(&request).expect("request serializes"));
+    }
+
+    /// Results published so far, oldest first
+    pub fn results(&self) -> Vec<QueuedResult> {
+        self.results.lock().unwrap().iter().filter_map(|message| serde_json::from_slice(message).ok()).collect()
+    }
+}
+
+impl MessageQueue for InMemoryQueue {
+    fn receive(&mut self) -> Option<Vec<u8>> {
+        self.requests.lock().unwrap().pop_front()
+    }
+
+    fn publish(&mut self, message: Vec<u8>) -> Result<(), Box<dyn Error>> {
+        self.results.lock().unwrap().push(message);
+        Ok(())
+    }
+}
@@ -0,0 +1,255 @@
+use {
+    crate::{
+        dead_letter::{dead_letter_path, DeadLetter, DeadLetterStore},
+        state_file::{
+            append_processed_request, load_processed_journal, load_processed_requests, processed_journal_path,
+            quarantine_state_file, save_processed_requests,
+        },
+    },
+    log::{debug, error, info, warn},
+    std::{collections::HashMap, error::Error, path::PathBuf, time::Duration},
+};
+
+/// Result of claiming a request for fulfillment
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InFlightClaim {
+    /// Nobody was fulfilling the request
+    Claimed,
+    /// A claim older than the TTL was taken over; holds its age
+    TakenOver(Duration),
+    /// Another worker holds a live claim
+    Held,
+}
+
+/// Where the server keeps which requests it processed, is fulfilling, or set
+/// aside, decoupled from how they're stored.
+///
+/// `FileRequestStore` keeps them next to the state file; oracles sharing their
+/// work can plug in a shared backend with `EnhancedVRFServer::with_request_store`.
+/// Times are unix seconds, and in-flight claims unix milliseconds, so they
+/// mean the same thing to every process.
+pub trait RequestStore: Send + Sync {
+    /// Restore persisted state. Returns true if it was lost, in which case
+    /// requests may already have results on-chain the store doesn't know of.
+    fn load(&mut self) -> Result<bool, Box<dyn Error>>;
+
+    /// Persist everything recorded so far
+    fn flush(&self) -> Result<(), Box<dyn Error>>;
+
+    fn is_processed(&self, request: &str) -> bool;
+
+    fn mark_processed(&mut self, request: &str) -> Result<(), Box<dyn Error>>;
+
+    fn processed_count(&self) -> usize;
+
+    /// Claim `request` for fulfillment at `now_millis`. A claim held for `ttl`
+    /// or more was left behind by a worker that died, and is taken over.
+    fn claim_in_flight(&mut self, request: &str, now_millis: u64, ttl: Duration) -> InFlightClaim;
+
+    fn release_in_flight(&mut self, request: &str);
+
+    fn in_flight_count(&self) -> usize;
+
+    /// Set `request` aside so it's never fulfilled again
+    fn dead_letter(&mut self, request: &str, timestamp: u64, reason: &str) -> Result<(), Box<dyn Error>>;
+
+    /// Why `request` was set aside, if it was
+    fn dead_letter_of(&self, request: &str) -> Option<DeadLetter>;
+
+    fn dead_letter_count(&self) -> usize;
+
+    fn is_dead_lettered(&self, request: &str) -> bool {
+        self.dead_letter_of(request).is_some()
+    }
+}
+
+/// A store that lives and dies with the process
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryRequestStore {
+    processed: HashMap<String, bool>,
+    /// Requests being fulfilled, with the unix time of their claim in milliseconds
+    in_flight: HashMap<String, u64>,
+    dead_letters: DeadLetterStore,
+}
+
+impl RequestStore for InMemoryRequestStore {
+    fn load(&mut self) -> Result<bool, Box<dyn Error>> {
+        Ok(false)
+    }
+
+    fn flush(&self) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+
+    fn is_processed(&self, request: &str) -> bool {
+        self.processed.contains_key(request)
+    }
+
+    fn mark_processed(&mut self, request: &str) -> Result<(), Box<dyn Error>> {
+        self.processed.insert(request.to_string(), true);
+        Ok(())
+    }
+
+    fn processed_count(&self) -> usize {
+        self.processed.len()
+    }
+
+    fn claim_in_flight(&mut self, request: &str, now_millis: u64, ttl: Duration) -> InFlightClaim {
+        let claim = match self.in_flight.get(request) {
+            None => InFlightClaim::Claimed,
+            Some(&claimed) if u128::from(now_millis.saturating_sub(claimed)) < ttl.as_millis() => {
+                return InFlightClaim::Held
+            }
+            Some(&claimed) => InFlightClaim::TakenOver(Duration::from_millis(now_millis.saturating_sub(claimed))),
+        };
+        self.in_flight.insert(request.to_string(), now_millis);
+        claim
+    }
+
+    fn release_in_flight(&mut self, request: &str) {
+        self.in_flight.remove(request);
+    }
+
+    fn in_flight_count(&self) -> usize {
+        self.in_flight.len()
+    }
+
+    fn dead_letter(&mut self, request: &str, timestamp: u64, reason: &str) -> Result<(), Box<dyn Error>> {
+        self.dead_letters.quarantine(request, timestamp, reason);
+        Ok(())
+    }
+
+    fn dead_letter_of(&self, request: &str) -> Option<DeadLetter> {
+        self.dead_letters.get(request)
+    }
+
+    fn dead_letter_count(&self) -> usize {
+        self.dead_letters.len()
+    }
+}
+
+/// The default store: processed requests are snapshotted to the state file and
+/// journaled in between, dead letters are kept next to it. Without a state
+/// file nothing outlives the process.
+#[derive(Debug, Clone, Default)]
+pub struct FileRequestStore {
+    state_file: Option<PathBuf>,
+    compress: bool,
+    memory: InMemoryRequestStore,
+}
+
+impl FileRequestStore {
+    pub fn new(state_file: Option<PathBuf>, compress: bool) -> Self {
+        Self { state_file, compress, memory: InMemoryRequestStore::default() }
+    }
+}
+
+impl RequestStore for FileRequestStore {
+    /// A corrupt state file is moved aside rather than failing startup; the
+    /// store then starts empty and reports the state as lost.
+    fn load(&mut self) -> Result<bool, Box<dyn Error>> {
+        let Some(path) = self.state_file.clone() else {
+            return Ok(false);
+        };
+        let mut lost = false;
+        match load_processed_requests(&path) {
+            Ok(processed) => {
+                self.memory.processed = processed;
+                info!("💾 Loaded {} processed requests from {:?}", self.memory.processed.len(), path);
+            }
+            Err(e) => {
+                let quarantined = quarantine_state_file(&path)?;
+                error!("🚨 CORRUPT STATE FILE {:?}: {}", path, e);
+                error!("🚨 Moved it to {:?}; starting with an empty cache and checking on-chain results before every fulfillment", quarantined);
+                self.memory.processed = HashMap::new();
+                lost = true;
+            }
+        }
+
+        // Fulfillments since the last snapshot
+        let journal_path = processed_journal_path(&path);
+        match load_processed_journal(&journal_path) {
+            Ok((requests, skipped)) => {
+                if skipped > 0 {
+                    warn!("⚠️  Skipped {} incomplete or invalid lines of journal {:?}", skipped, journal_path);
+                }
+                if !requests.is_empty() {
+                    info!("💾 Replaying {} processed requests from {:?}", requests.len(), journal_path);
+                }
+                let replayed = !requests.is_empty() || skipped > 0;
+                self.memory.processed.extend(requests.into_iter().map(|request| (request, true)));
+                // Fold the journal into a fresh snapshot, so new entries aren't
+                // appended to a partial line
+                if replayed {
+                    self.flush()?;
+                }
+            }
+            Err(e) => warn!("⚠️  Ignoring journal {:?}: {}", journal_path, e),
+        }
+
+        let dead_letter_path = dead_letter_path(&path);
+        match DeadLetterStore::load(&dead_letter_path) {
+            Ok(dead_letters) => self.memory.dead_letters = dead_letters,
+            Err(e) => warn!("⚠️  Ignoring dead-letter store {:?}: {}", dead_letter_path, e),
+        }
+        Ok(lost)
+    }
+
+    fn flush(&self) -> Result<(), Box<dyn Error>> {
+        let Some(path) = &self.state_file else {
+            return Ok(());
+        };
+        save_processed_requests(path, &self.memory.processed, self.compress)?;
+        // The snapshot now covers everything journaled
+        let journal_path = processed_journal_path(path);
+        if journal_path.exists() {
+            std::fs::remove_file(&journal_path)
+                .map_err(|e| format!("Failed to truncate journal {:?}: {}", journal_path, e))?;
+        }
+        self.memory.dead_letters.save(&dead_letter_path(path), self.compress)?;
+        debug!("💾 Persisted {} processed requests to {:?}", self.memory.processed.len(), path);
+        Ok(())
+    }
+
+    fn is_processed(&self, request: &str) -> bool {
+        self.memory.is_processed(request)
+    }
+
+    /// Journaled right away, so a crash before the next snapshot doesn't lose it
+    fn mark_processed(&mut self, request: &str) -> Result<(), Box<dyn Error>> {
+        self.memory.mark_processed(request)?;
+        if let Some(path) = &self.state_file {
+            append_processed_request(&processed_journal_path(path), request)?;
+        }
+        Ok(())
+    }
+
+    fn processed_count(&self) -> usize {
+        self.memory.processed_count()
+    }
+
+    /// Claims aren't persisted: after a restart nothing is in flight
+    fn claim_in_flight(&mut self, request: &str, now_millis: u64, ttl: Duration) -> InFlightClaim {
+        self.memory.claim_in_flight(request, now_millis, ttl)
+    }
+
+    fn release_in_flight(&mut self, request: &str) {
+        self.memory.release_in_flight(request)
+    }
+
+    fn in_flight_count(&self) -> usize {
+        self.memory.in_flight_count()
+    }
+
+    fn dead_letter(&mut self, request: &str, timestamp: u64, reason: &str) -> Result<(), Box<dyn Error>> {
+        self.memory.dead_letter(request, timestamp, reason)
+    }
+
+    fn dead_letter_of(&self, request: &str) -> Option<DeadLetter> {
+        self.memory.dead_letter_of(request)
+    }
+
+    fn dead_letter_count(&self) -> usize {
+        self.memory.dead_letter_count()
+    }
+}
@@ -0,0 +1,248 @@
+use {
+    crate::{
+        clock::{Clock, SystemClock},
+        enhanced_vrf_server::redact_rpc_url,
+    },
+    log::{debug, warn},
+    solana_client::{
+        client_error::{ClientError, ClientErrorKind},
+        rpc_client::RpcClient,
+        rpc_request::RpcError,
+    },
+    solana_sdk::commitment_config::CommitmentConfig,
+    std::{
+        str::FromStr,
+        sync::{Arc, Mutex},
+        time::{Duration, Instant},
+    },
+};
+
+/// How long an endpoint that returned an error is taken out of rotation
+pub const RPC_DEMOTION_PERIOD: Duration = Duration::from_secs(30);
+
+/// JSON-RPC error code of a node that is behind or otherwise unhealthy
+const NODE_UNHEALTHY: i64 = -32005;
+
+/// Whether `error` is the endpoint's fault: unreachable, timed out, a 5xx, a
+/// garbled response or an unhealthy node. Errors about the request itself,
+/// such as a failed transaction, say nothing about the endpoint.
+pub fn is_endpoint_failure(error: &ClientError) -> bool {
+    match error.kind() {
+        ClientErrorKind::Io(_) | ClientErrorKind::SerdeJson(_) => true,
+        ClientErrorKind::Reqwest(e) => e.status().is_none_or(|status| status.is_server_error()),
+        ClientErrorKind::RpcError(RpcError::RpcRequestError(_)) => true,
+        ClientErrorKind::RpcError(RpcError::RpcResponseError { code, .. }) => *code == NODE_UNHEALTHY,
+        _ => false,
+    }
+}
+
+/// An RPC endpoint and its share of the traffic.
+///
+/// Parsed from `<url>` or `<url>#<weight>`; the weight defaults to 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RpcEndpoint {
+    pub url: String,
+    pub weight: u32,
+}
+
+impl FromStr for RpcEndpoint {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (url, weight) = match s.rsplit_once('#') {
+            Some((url, weight)) => {
+                let weight = weight
+                    .parse::<u32>()
+                    .map_err(|e| format!("invalid weight '{}' for RPC endpoint {}: {}", weight, redact_rpc_url(url), e))?;
+                (url, weight)
+            }
+            None => (s, 1),
+        };
+        if url.is_empty() {
+            return Err("RPC endpoint URL must not be empty".to_string());
+        }
+        if weight == 0 {
+            return Err(format!("weight of RPC endpoint {} must be at least 1", redact_rpc_url(url)));
+        }
+        Ok(RpcEndpoint { url: url.to_string(), weight })
+    }
+}
+
+#[derive(Debug)]
+struct EndpointState {
+    /// Running smooth-weighted-round-robin score
+    current: i64,
+    demoted_until: Option<Instant>,
+}
+
+/// Spreads RPC calls over several endpoints in proportion to their weights
+/// (smooth weighted round-robin), taking endpoints that fail out of
+/// rotation for `RPC_DEMOTION_PERIOD`
+pub struct RpcPool {
+    clients: Vec<(Arc<RpcClient>, u32)>,
+    state: Mutex<Vec<EndpointState>>,
+    clock: Arc<dyn Clock>,
+}
+
+impl RpcPool {
+    pub fn new(endpoints: &[RpcEndpoint], commitment: CommitmentConfig) -> Self {
+        Self::from_clients(
+            endpoints
+                .iter()
+                .map(|endpoint| {
+                    let client = RpcClient::new_with_commitment(endpoint.url.clone(), commitment);
+                    (Arc::new(client), endpoint.weight)
+                })
+                .collect(),
+        )
+    }
+
+    /// Build a pool from existing clients and their weights
+    pub fn from_clients(clients: Vec<(Arc<RpcClient>, u32)>) -> Self {
+        assert!(!clients.is_empty(), "an RPC pool needs at least one endpoint");
+        let state = clients
+            .iter()
+            .map(|_| EndpointState { current: 0, demoted_until: None })
+            .collect();
+        Self {
+            clients,
+            state: Mutex::new(state),
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Number of endpoints in the pool
+    pub fn len(&self) -> usize {
+        self.clients.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.clients.is_empty()
+    }
+
+    /// The first endpoint, used for calls that aren't load balanced
+    pub fn primary(&self) -> Arc<RpcClient> {
+        Arc::clone(&self.clients[0].0)
+    }
+
+    /// The endpoint at `index`
+    pub fn client(&self, index: usize) -> Arc<RpcClient> {
+        Arc::clone(&self.clients[index].0)
+    }
+
+    /// Pick the next endpoint by weight, skipping demoted ones unless all are demoted
+    pub fn select(&self) -> usize {
+        let now = self.clock.now_instant();
+        let mut state = self.state.lock().unwrap();
+        for endpoint in state.iter_mut() {
+            if endpoint.demoted_until.is_some_and(|until| now >= until) {
+                endpoint.demoted_until = None;
+            }
+        }
+        let all_demoted = state.iter().all(|endpoint| endpoint.demoted_until.is_some());
+        let eligible: Vec<usize> = (0..state.len())
+            .filter(|&index| all_demoted || state[index].demoted_until.is_none())
+            .collect();
+
+        let mut total = 0;
+        for &index in &eligible {
+            let weight = self.clients[index].1 as i64;
+            state[index].current += weight;
+            total += weight;
+        }
+        // Highest score wins, ties go to the earlier endpoint
+        let best = eligible
+            .into_iter()
+            .max_by(|&a, &b| state[a].current.cmp(&state[b].current).then(b.cmp(&a)))
+            .expect("at least one endpoint is eligible");
+        state[best].current -= total;
+        best
+    }
+
+    /// Record the result of a call to endpoint `index`
+    pub fn report(&self, index: usize, ok: bool) {
+        if ok {
+            return;
+        }
+        let mut state = self.state.lock().unwrap();
+        let until = self.clock.now_instant() + RPC_DEMOTION_PERIOD;
+        if state[index].demoted_until.is_none() {
+            warn!("⚠️  Demoting RPC endpoint {} for {:?} after an error", redact_rpc_url(&self.clients[index].0.url()), RPC_DEMOTION_PERIOD);
+        }
+        state[index].demoted_until = Some(until);
+    }
+
+    /// Run `call` against the next endpoint and record whether the endpoint failed it
+    pub fn call<T, F>(&self, call: F) -> Result<T, ClientError>
+    where
+        F: FnOnce(&RpcClient) -> Result<T, ClientError>,
+    {
+        let index = self.select();
+        debug!("Routing RPC call to endpoint {}", index);
+        let result = call(&self.clients[index].0);
+        self.report(index, !matches!(&result, Err(e) if is_endpoint_failure(e)));
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::clock::MockClock};
+
+    #[test]
+    fn test_weighted_distribution_and_demotion() {
+        let clock = Arc::new(MockClock::new(0));
+        let pool = RpcPool::from_clients(vec![
+            (Arc::new(RpcClient::new_mock("succeeds".to_string())), 3),
+            (Arc::new(RpcClient::new_mock("fails".to_string())), 1),
+        ])
+        .with_clock(clock.clone());
+
+        let mut counts = [0usize; 2];
+        for _ in 0..400 {
+            counts[pool.select()] += 1;
+        }
+        assert_eq!(counts, [300, 100]);
+
+        // The failing endpoint is taken out of rotation after its first error
+        while pool.call(|rpc| rpc.get_slot()).is_ok() {}
+        assert!((0..50).all(|_| pool.select() == 0));
+
+        clock.advance(RPC_DEMOTION_PERIOD);
+        assert!((0..4).any(|_| pool.select() == 1));
+
+        assert_eq!("https://rpc.example.com/?key=a#5".parse::<RpcEndpoint>().unwrap().weight, 5);
+        assert_eq!("http://localhost:8899".parse::<RpcEndpoint>().unwrap().weight, 1);
+        assert!("http://localhost:8899#0".parse::<RpcEndpoint>().is_err());
+    }
+
+    #[test]
+    fn test_request_errors_do_not_demote_the_endpoint() {
+        use solana_sdk::transaction::TransactionError;
+
+        let pool = RpcPool::from_clients(vec![
+            (Arc::new(RpcClient::new_mock("succeeds".to_string())), 1),
+            (Arc::new(RpcClient::new_mock("succeeds".to_string())), 1),
+        ]);
+        for _ in 0..4 {
+            let rejected = pool.call(|_| -> Result<(), ClientError> {
+                Err(ClientErrorKind::TransactionError(TransactionError::InsufficientFundsForRent { account_index: 0 }).into())
+            });
+            assert!(rejected.is_err());
+        }
+        // Both endpoints stay in rotation
+        let mut counts = [0usize; 2];
+        for _ in 0..4 {
+            counts[pool.select()] += 1;
+        }
+        assert_eq!(counts, [2, 2]);
+
+        assert!(is_endpoint_failure(&ClientErrorKind::Io(std::io::ErrorKind::TimedOut.into()).into()));
+        assert!(!is_endpoint_failure(&ClientErrorKind::Custom("custom program error: 0x1".to_string()).into()));
+    }
+}
@@ -0,0 +1,127 @@
+use {
+    crate::cli_integration::CLIError,
+    log::debug,
+    std::{
+        io::{Read, Write},
+        process::{Command, Stdio},
+        thread,
+        time::{Duration, Instant},
+    },
+};
+
+/// Default time an external seed hook may run before it is killed
+pub const DEFAULT_SEED_HOOK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How often a running hook is checked for completion
+const SEED_HOOK_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// External command that turns a request seed into the VRF input.
+///
+/// The command is run through `sh -c`, receives the seed hex encoded on
+/// stdin and must print the VRF input hex encoded on stdout, mirroring the
+/// hex interface of `ecvrf-cli`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SeedHook {
+    pub command: String,
+    pub timeout: Duration,
+}
+
+impl SeedHook {
+    pub fn new(command: impl Into<String>) -> Self {
+        Self {
+            command: command.into(),
+            timeout: DEFAULT_SEED_HOOK_TIMEOUT,
+        }
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Pipe `seed` through the hook and return the transformed VRF input
+    pub fn apply(&self, seed: &[u8]) -> Result<Vec<u8>, CLIError> {
+        debug!("Running seed hook '{}' on seed {}", self.command, hex::encode(seed));
+
+        let mut child = Command::new("sh")
+            .arg("-c")
+            .arg(&self.command)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(CLIError::ProcessError)?;
+
+        // Closing stdin signals end of input to the hook
+        let mut stdin = child.stdin.take().expect("stdin is piped");
+        if let Err(e) = stdin.write_all(hex::encode(seed).as_bytes()) {
+            // A hook may exit without reading its input; its status decides below
+            debug!("Seed hook did not consume its input: {}", e);
+        }
+        drop(stdin);
+
+        let deadline = Instant::now() + self.timeout;
+        let status = loop {
+            if let Some(status) = child.try_wait().map_err(CLIError::ProcessError)? {
+                break status;
+            }
+            if Instant::now() >= deadline {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(CLIError::ProofGenerationFailed(format!(
+                    "Seed hook '{}' timed out after {:?}", self.command, self.timeout
+                )));
+            }
+            thread::sleep(SEED_HOOK_POLL_INTERVAL);
+        };
+
+        let mut stdout = String::new();
+        let mut stderr = String::new();
+        if let Some(mut out) = child.stdout.take() {
+            out.read_to_string(&mut stdout).map_err(CLIError::ProcessError)?;
+        }
+        if let Some(mut err) = child.stderr.take() {
+            let _ = err.read_to_string(&mut stderr);
+        }
+
+        if !status.success() {
+            return Err(CLIError::ProofGenerationFailed(format!(
+                "Seed hook '{}' failed ({}): {}", self.command, status, stderr.trim()
+            )));
+        }
+
+        let input = hex::decode(stdout.trim()).map_err(|e| CLIError::InvalidOutput(format!(
+            "Seed hook '{}' printed invalid hex: {}", self.command, e
+        )))?;
+        if input.is_empty() {
+            return Err(CLIError::InvalidOutput(format!(
+                "Seed hook '{}' produced an empty VRF input", self.command
+            )));
+        }
+        Ok(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seed_hook_errors_and_timeout() {
+        assert_eq!(SeedHook::new("cat").apply(&[1, 2, 3]).unwrap(), vec![1, 2, 3]);
+
+        let err = SeedHook::new("echo boom >&2; exit 3").apply(&[1]).unwrap_err();
+        assert!(err.to_string().contains("boom"), "{}", err);
+
+        let err = SeedHook::new("echo not-hex").apply(&[1]).unwrap_err();
+        assert!(err.to_string().contains("invalid hex"), "{}", err);
+
+        let started = Instant::now();
+        let err = SeedHook::new("sleep 5")
+            .with_timeout(Duration::from_millis(100))
+            .apply(&[1])
+            .unwrap_err();
+        assert!(err.to_string().contains("timed out"), "{}", err);
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+}
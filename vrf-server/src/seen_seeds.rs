@@ -0,0 +1,57 @@
+use {
+    crate::state_file::{load_json_state, save_json_state, sibling_path},
+    serde::{Deserialize, Serialize},
+    std::{
+        collections::HashMap,
+        error::Error,
+        path::{Path, PathBuf},
+    },
+};
+
+/// Seeds of fulfilled requests with when they were fulfilled, for rejecting
+/// replays of a seed within a time window
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SeenSeeds {
+    /// Hex seed to unix timestamp of its latest fulfillment
+    seeds: HashMap<String, u64>,
+}
+
+impl SeenSeeds {
+    pub fn record(&mut self, seed: &[u8], timestamp: u64) {
+        self.seeds.insert(hex::encode(seed), timestamp);
+    }
+
+    /// Whether `seed` was fulfilled less than `window_secs` before `now`
+    pub fn seen_within(&self, seed: &[u8], now: u64, window_secs: u64) -> bool {
+        self.seeds
+            .get(&hex::encode(seed))
+            .is_some_and(|&seen| now.saturating_sub(seen) < window_secs)
+    }
+
+    /// Forget seeds fulfilled `window_secs` or more before `now`
+    pub fn prune(&mut self, now: u64, window_secs: u64) {
+        self.seeds.retain(|_, seen| now.saturating_sub(*seen) < window_secs);
+    }
+
+    pub fn len(&self) -> usize {
+        self.seeds.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.seeds.is_empty()
+    }
+
+    pub fn save(&self, path: &Path, compress: bool) -> Result<(), Box<dyn Error>> {
+        save_json_state(path, self, compress)
+    }
+
+    /// Load saved seeds; a missing file is an empty store
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        load_json_state(path, "seen seeds")
+    }
+}
+
+/// Where the seen seeds of a state file are kept
+pub fn seen_seeds_path(state_file: &Path) -> PathBuf {
+    sibling_path(state_file, ".seeds")
+}
@@ -29,6 +29,11 @@ pub struct Subscription {
     pub confirmations: u8,
     /// Nonce for request ID generation
     pub nonce: u64,
+    /// VRF public keys of the oracles the owner authorized to serve this
+    /// subscription, on coordinators that support it. Stored as an optional
+    /// trailing field that older coordinators omit, so it's decoded separately.
+    #[borsh(skip)]
+    pub authorized_oracles: Option<Vec<[u8; 32]>>,
 }
 
 #[derive(BorshSerialize, BorshDeserialize, Debug)]
@@ -53,6 +58,11 @@ pub struct RandomnessRequest {
     pub nonce: u64,
     /// Commitment hash of request parameters
     pub commitment: [u8; 32],
+    /// VRF public key of the oracle designated to fulfill this request, on
+    /// coordinators that assign requests. Stored as an optional trailing
+    /// field that unassigning coordinators omit, so it's decoded separately.
+    #[borsh(skip)]
+    pub assigned_oracle: Option<[u8; 32]>,
 }
 
 #[derive(BorshSerialize, BorshDeserialize, Debug)]
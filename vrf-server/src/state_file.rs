@@ -0,0 +1,155 @@
+use {
+    flate2::{read::GzDecoder, write::GzEncoder, Compression},
+    serde::{de::DeserializeOwned, Serialize},
+    solana_sdk::pubkey::Pubkey,
+    std::{
+        collections::HashMap,
+        error::Error,
+        fs::{self, OpenOptions},
+        io::{Read, Write},
+        path::{Path, PathBuf},
+        str::FromStr,
+    },
+};
+
+/// Leading bytes of every gzip stream, used to detect compressed state on load
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Write `contents` to `path`, gzip-compressed if requested.
+///
+/// Writes go through a temporary sibling file and a rename so a crash never
+/// leaves a half-written state file behind.
+pub fn write_state_file(path: &Path, contents: &[u8], compress: bool) -> Result<(), Box<dyn Error>> {
+    let bytes = if compress {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(contents)?;
+        encoder.finish()?
+    } else {
+        contents.to_vec()
+    };
+
+    let tmp_path = path.with_extension("tmp");
+    fs::write(&tmp_path, bytes).map_err(|e| format!("Failed to write state file {:?}: {}", tmp_path, e))?;
+    fs::rename(&tmp_path, path).map_err(|e| format!("Failed to replace state file {:?}: {}", path, e))?;
+    Ok(())
+}
+
+/// Read a state file, transparently decompressing it if it is gzipped
+pub fn read_state_file(path: &Path) -> Result<Vec<u8>, Box<dyn Error>> {
+    let bytes = fs::read(path).map_err(|e| format!("Failed to read state file {:?}: {}", path, e))?;
+    if !bytes.starts_with(&GZIP_MAGIC) {
+        return Ok(bytes);
+    }
+
+    let mut contents = Vec::new();
+    GzDecoder::new(&bytes[..])
+        .read_to_end(&mut contents)
+        .map_err(|e| format!("Failed to decompress state file {:?}: {}", path, e))?;
+    Ok(contents)
+}
+
+/// Persist `state` as JSON through `write_state_file`
+pub fn save_json_state<T: Serialize>(path: &Path, state: &T, compress: bool) -> Result<(), Box<dyn Error>> {
+    write_state_file(path, &serde_json::to_vec(state)?, compress)
+}
+
+/// Load JSON state saved by `save_json_state`; a missing file is the default
+/// state. `what` names the state in errors.
+pub fn load_json_state<T: DeserializeOwned + Default>(path: &Path, what: &str) -> Result<T, Box<dyn Error>> {
+    if !path.exists() {
+        return Ok(T::default());
+    }
+    let contents = read_state_file(path)?;
+    Ok(serde_json::from_slice(&contents).map_err(|e| format!("Invalid {} {:?}: {}", what, path, e))?)
+}
+
+/// Where a side store of `state_file` is kept: its path with `suffix` appended
+pub fn sibling_path(state_file: &Path, suffix: &str) -> PathBuf {
+    let mut path = state_file.as_os_str().to_owned();
+    path.push(suffix);
+    PathBuf::from(path)
+}
+
+/// Persist the processed-request cache as JSON
+pub fn save_processed_requests(
+    path: &Path,
+    processed_requests: &HashMap<String, bool>,
+    compress: bool,
+) -> Result<(), Box<dyn Error>> {
+    save_json_state(path, processed_requests, compress)
+}
+
+/// Load the processed-request cache; a missing file is an empty cache
+pub fn load_processed_requests(path: &Path) -> Result<HashMap<String, bool>, Box<dyn Error>> {
+    load_json_state(path, "state file")
+}
+
+/// Where requests processed since the last snapshot of a state file are journaled
+pub fn processed_journal_path(state_file: &Path) -> PathBuf {
+    sibling_path(state_file, ".journal")
+}
+
+/// Append a processed request to the journal, one request per line, and sync
+/// it so the fulfillment survives a crash before the next snapshot
+pub fn append_processed_request(path: &Path, request: &str) -> Result<(), Box<dyn Error>> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("Failed to open journal {:?}: {}", path, e))?;
+    file.write_all(format!("{}\n", request).as_bytes())?;
+    file.sync_data()?;
+    Ok(())
+}
+
+/// Requests in the journal, and how many lines were skipped. Only complete
+/// lines holding a request address count, so a line cut short by a crash is
+/// skipped rather than failing the load.
+pub fn load_processed_journal(path: &Path) -> Result<(Vec<String>, usize), Box<dyn Error>> {
+    if !path.exists() {
+        return Ok((Vec::new(), 0));
+    }
+    let contents = fs::read(path).map_err(|e| format!("Failed to read journal {:?}: {}", path, e))?;
+    let mut requests = Vec::new();
+    let mut skipped = 0;
+    for line in String::from_utf8_lossy(&contents).split_inclusive('\n') {
+        match line.strip_suffix('\n') {
+            Some(request) if Pubkey::from_str(request).is_ok() => requests.push(request.to_string()),
+            _ => skipped += 1,
+        }
+    }
+    Ok((requests, skipped))
+}
+
+/// Move a state file that failed to load aside, so it is kept for
+/// inspection without being loaded again. Returns where it was moved.
+pub fn quarantine_state_file(path: &Path) -> Result<PathBuf, Box<dyn Error>> {
+    let mut quarantined = path.as_os_str().to_owned();
+    quarantined.push(format!(".corrupt-{}", chrono::Utc::now().format("%Y%m%dT%H%M%S%.3fZ")));
+    let quarantined = PathBuf::from(quarantined);
+    fs::rename(path, &quarantined)
+        .map_err(|e| format!("Failed to quarantine state file {:?}: {}", path, e))?;
+    Ok(quarantined)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compressed_state_round_trip() {
+        let cache: HashMap<String, bool> = (0..100)
+            .map(|i| (format!("request-{}", i), true))
+            .collect();
+
+        for compress in [true, false] {
+            let path = std::env::temp_dir().join(format!("kamui-state-{}.json", rand::random::<u64>()));
+            save_processed_requests(&path, &cache, compress).unwrap();
+
+            let raw = fs::read(&path).unwrap();
+            assert_eq!(raw.starts_with(&GZIP_MAGIC), compress);
+            assert_eq!(load_processed_requests(&path).unwrap(), cache);
+            fs::remove_file(&path).unwrap();
+        }
+    }
+}
@@ -0,0 +1,108 @@
+use {
+    crate::enhanced_vrf_server::{redact_rpc_url, FulfillmentOutcome},
+    log::{debug, warn},
+    serde::Serialize,
+    solana_client::client_error::reqwest,
+    std::time::Duration,
+};
+
+/// Time allowed for a single webhook delivery attempt
+pub const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Delivery attempts per outcome before it is dropped
+pub const WEBHOOK_MAX_ATTEMPTS: u32 = 3;
+
+/// Pause before retrying a failed delivery, doubled on each retry
+const WEBHOOK_RETRY_BACKOFF: Duration = Duration::from_millis(500);
+
+/// JSON body POSTed for every processed request
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WebhookPayload {
+    pub request: String,
+    pub output: Option<String>,
+    pub signature: Option<String>,
+    /// `fulfilled` or `failed`
+    pub status: &'static str,
+    pub error: Option<String>,
+    /// Oracle-signed attestation of the result, when enabled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attestation: Option<String>,
+}
+
+impl From<&FulfillmentOutcome> for WebhookPayload {
+    fn from(outcome: &FulfillmentOutcome) -> Self {
+        Self {
+            request: outcome.request.clone(),
+            output: outcome.result.clone(),
+            signature: outcome.signature.clone(),
+            status: if outcome.is_success() { "fulfilled" } else { "failed" },
+            error: outcome.error.clone(),
+            attestation: outcome.attestation.clone(),
+        }
+    }
+}
+
+/// Outbound webhook notified of every fulfillment outcome.
+///
+/// Deliveries run on their own task with a per-attempt timeout and bounded
+/// retries, so a slow or failing endpoint never holds up fulfillment.
+#[derive(Clone)]
+pub struct Webhook {
+    url: String,
+    client: reqwest::Client,
+}
+
+impl Webhook {
+    pub fn new(url: impl Into<String>) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(WEBHOOK_TIMEOUT)
+            .build()
+            .unwrap_or_default();
+        Self { url: url.into(), client }
+    }
+
+    /// Deliver `outcome` in the background
+    pub fn notify(&self, outcome: &FulfillmentOutcome) -> tokio::task::JoinHandle<()> {
+        let webhook = self.clone();
+        let payload = WebhookPayload::from(outcome);
+        tokio::spawn(async move {
+            if let Err(e) = webhook.deliver(&payload).await {
+                warn!("⚠️  Dropping webhook for request {}: {}", payload.request, e);
+            }
+        })
+    }
+
+    /// POST `payload`, retrying failed attempts with backoff
+    pub async fn deliver(&self, payload: &WebhookPayload) -> Result<(), String> {
+        let body = serde_json::to_vec(payload).map_err(|e| e.to_string())?;
+        let mut backoff = WEBHOOK_RETRY_BACKOFF;
+        let mut last_error = String::new();
+
+        for attempt in 1..=WEBHOOK_MAX_ATTEMPTS {
+            let result = self.client
+                .post(&self.url)
+                .header("content-type", "application/json")
+                .body(body.clone())
+                .send()
+                .await;
+            match result {
+                Ok(response) if response.status().is_success() => {
+                    debug!("Webhook {} accepted outcome of {}", redact_rpc_url(&self.url), payload.request);
+                    return Ok(());
+                }
+                Ok(response) => last_error = format!("HTTP {}", response.status()),
+                // reqwest errors embed the full URL, which may carry a token
+                Err(e) => last_error = e.without_url().to_string(),
+            }
+            warn!(
+                "⚠️  Webhook {} attempt {}/{} failed: {}",
+                redact_rpc_url(&self.url), attempt, WEBHOOK_MAX_ATTEMPTS, last_error
+            );
+            if attempt < WEBHOOK_MAX_ATTEMPTS {
+                tokio::time::sleep(backoff).await;
+                backoff *= 2;
+            }
+        }
+        Err(format!("{} failed after {} attempts: {}", redact_rpc_url(&self.url), WEBHOOK_MAX_ATTEMPTS, last_error))
+    }
+}